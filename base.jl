@@ -1,7 +1,3 @@
-(def [nil] [])
-(def [true] [1])
-(def [false] [0])
-
 ;; basic func def
 
 
@@ -14,12 +10,6 @@
 (def [curry] unpack)
 (def [uncurry] pack)
 
-(def [do] (\ [& l] [
-  if (== l nil)
-    [nil]
-    [last l]
-]))
-
 (def [let] (\ [b] [
   ((\[_] b) () )
 ]))
@@ -42,7 +32,7 @@
 
 (def [take] (\ [n l] [
   if (== n 0)
-    [nil]
+    [[]]
     [join (head l) (take (- n 1) (tail l))]
 ]))
 
@@ -58,7 +48,7 @@
 ]))
 
 (def [contains] (\ [x l] [
-  if (== l nil)
+  if (== l [])
     [false]
     [if (== x (first l))
       [true]
@@ -68,21 +58,49 @@
 
 ; basic functional list funcs
 
-(def [map] (\ [f l] [
-  if (== l nil)
-    [nil]
-    [join (list(f (first l))) (map f(tail l))]
+; map and filter go through the list a chunk at a time: a chunk is built by
+; plain recursion, which only gets as deep as the chunk is long, and the
+; chunks are joined once at the end. So long lists neither overflow the
+; stack nor get the result copied for every element.
+(def [map-chunk] (\ [f n l] [
+  if (or (== n 0) (== l []))
+    [[]]
+    [join (list (f (first l))) (map-chunk f (- n 1) (tail l))]
+]))
+
+(def [filter-chunk] (\ [p n l] [
+  if (or (== n 0) (== l []))
+    [[]]
+    [join (if (p (first l)) [head l] [[]])
+      (filter-chunk p (- n 1) (tail l))]
+]))
+
+; joins neighbouring lists until one is left, so each element is only
+; copied once per halving
+(def [join-all] (\ [ls] [
+  if (< (len ls) 2)
+    [if (== ls []) [[]] [last ls]]
+    [join-all (join-pairs ls [])]
+]))
+
+(def [join-pairs] (\ [ls acc] [
+  if (< (len ls) 2)
+    [join acc ls]
+    [join-pairs (tail (tail ls))
+      (join acc (list (join (last (head ls)) (last (head (tail ls))))))]
 ]))
 
-(def [filter] (\ [p l] [
-  if (== l nil)
-    [nil]
-    [join (if (p (first l)) [head l] [nil])
-      (filter p (tail l))]
+(def [by-chunks] (\ [g f l acc] [
+  if (== l [])
+    [join-all acc]
+    [by-chunks g f (drop (min 64 (len l)) l) (join acc (list (g f 64 l)))]
 ]))
 
+(def [map] (\ [f l] [by-chunks map-chunk f l []]))
+(def [filter] (\ [p l] [by-chunks filter-chunk p l []]))
+
 (def [foldl] (\ [f i l] [
-  if (== l nil)
+  if (== l [])
     [i]
     [foldl f (f i (first l)) (tail l)]
 ]))
@@ -92,7 +110,7 @@
 
 ; control-flow
 (def [select] (\ [& cs] [
-  if (== cs nil)
+  if (== cs [])
     [error "No selection found"]
     [if (first (first cs))
       [second (first cs)]
@@ -100,7 +118,7 @@
     ]  
 ]))
 (def [case] (\ [x & cs] [
-  if (== cs nil)
+  if (== cs [])
     [error "No case found"]
     [if (== x (first (first cs)))
       [second (first cs)]
@@ -109,3 +127,14 @@
 ]))
 
 
+
+(defmacro [when c & body] `(if ,c [do ,@body] [nil]))
+(defmacro [unless c & body] `(if ,c [nil] [do ,@body]))
+
+; thread x through forms as their first argument, (-> x (f a) (g b)) is (g (f x a) b)
+(defmacro [-> x & forms] [
+  if (== forms [])
+    [x]
+    [`(-> (->step ,x ,@(head forms)) ,@(tail forms))]
+])
+(defmacro [->step x form] `(,@(head form) ,x ,@(tail form)))
@@ -0,0 +1,244 @@
+#ifndef JLISP_H
+#define JLISP_H
+
+/* Generated by cbindgen from src/lib.rs, don't edit. Regenerate with
+   UPDATE_HEADER=1 cargo test -p jlisp-capi --test header */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// What a `JlispValue` holds
+typedef enum JlispType {
+  JLISP_TYPE_NIL,
+  JLISP_TYPE_BOOL,
+  // An integer, `jlisp_value_int` fails for the ones beyond 64 bits
+  JLISP_TYPE_NUMBER,
+  JLISP_TYPE_RATIONAL,
+  JLISP_TYPE_FLOAT,
+  JLISP_TYPE_CHAR,
+  JLISP_TYPE_STRING,
+  JLISP_TYPE_SYMBOL,
+  JLISP_TYPE_LIST,
+  JLISP_TYPE_MAP,
+  JLISP_TYPE_SET,
+  // A builtin, lambda or macro
+  JLISP_TYPE_FUNCTION,
+  JLISP_TYPE_OTHER,
+} JlispType;
+
+typedef struct JlispInterp JlispInterp;
+
+typedef struct JlispValue JlispValue;
+
+// A builtin implemented in C. `args` are borrowed for the call, the value
+// returned is taken over by the interpreter, a `jlisp_raise` value raises
+// an error and NULL raises a generic one. It may call back into the
+// interpreter running it, but not free it.
+typedef struct JlispValue *(*JlispFn)(void *userdata,
+                                      const struct JlispValue *const *args,
+                                      size_t nargs);
+
+// A new interpreter with the builtins and the prelude
+struct JlispInterp *jlisp_new(void);
+
+// A new interpreter with only the builtins
+struct JlispInterp *jlisp_new_bare(void);
+
+// # Safety
+// `interp` is NULL or from `jlisp_new`, and isn't used after
+void jlisp_free(struct JlispInterp *interp);
+
+// Every form in `src` evaluated in order, the value of the last, or NULL
+// if one fails
+//
+// # Safety
+// `interp` is from `jlisp_new`, `src` a NUL-terminated string
+struct JlispValue *jlisp_eval(struct JlispInterp *interp, const char *src);
+
+// The global `name` called with the `nargs` values in `args`, which stay
+// the caller's, or NULL if the call fails
+//
+// # Safety
+// `interp` is from `jlisp_new`, `name` a NUL-terminated string and `args`
+// points at `nargs` values
+struct JlispValue *jlisp_call(struct JlispInterp *interp,
+                              const char *name,
+                              const struct JlispValue *const *args,
+                              size_t nargs);
+
+// The value of the global `name`, or NULL if it isn't bound
+//
+// # Safety
+// `interp` is from `jlisp_new`, `name` a NUL-terminated string
+struct JlispValue *jlisp_get_global(struct JlispInterp *interp, const char *name);
+
+// Bind the global `name` to `value`, which stays the caller's
+//
+// # Safety
+// `interp` is from `jlisp_new`, `name` a NUL-terminated string and `value`
+// a live value
+bool jlisp_set_global(struct JlispInterp *interp, const char *name, const struct JlispValue *value);
+
+// Bind `name` to a builtin calling `f` with `userdata`. It takes
+// `min_args` arguments, or at least that many if `variadic`.
+//
+// # Safety
+// `interp` is from `jlisp_new`, `name` a NUL-terminated string and
+// `userdata` whatever `f` expects, for as long as the interpreter lives and
+// on any thread it is used from
+bool jlisp_register(struct JlispInterp *interp,
+                    const char *name,
+                    size_t min_args,
+                    bool variadic,
+                    JlispFn f,
+                    void *userdata);
+
+// The message of the error the last call on `interp` failed with, NULL if
+// it didn't fail. It's valid until the next call.
+//
+// # Safety
+// `interp` is from `jlisp_new`
+const char *jlisp_error_message(const struct JlispInterp *interp);
+
+// The kind of that error, as `(get e "kind")` in a `catch` gives it
+//
+// # Safety
+// `interp` is from `jlisp_new`
+const char *jlisp_error_kind(const struct JlispInterp *interp);
+
+struct JlispValue *jlisp_nil(void);
+
+struct JlispValue *jlisp_bool(bool b);
+
+struct JlispValue *jlisp_int(int64_t n);
+
+struct JlispValue *jlisp_float(double f);
+
+// A String value, NULL if `s` isn't UTF-8
+//
+// # Safety
+// `s` is a NUL-terminated string
+struct JlispValue *jlisp_string(const char *s);
+
+// A List of the `n` values in `items`, which stay the caller's
+//
+// # Safety
+// `items` points at `n` values
+struct JlispValue *jlisp_list(const struct JlispValue *const *items, size_t n);
+
+// A value that, returned from a callback, raises an error with `msg`
+//
+// # Safety
+// `msg` is a NUL-terminated string
+struct JlispValue *jlisp_raise(const char *msg);
+
+// # Safety
+// `v` is NULL or a value the caller owns, and isn't used after
+void jlisp_value_free(struct JlispValue *v);
+
+// # Safety
+// `v` is a live value
+enum JlispType jlisp_value_type(const struct JlispValue *v);
+
+// Whether `v` is true as a condition, see `Expr::is_truthy`
+//
+// # Safety
+// `v` is a live value
+bool jlisp_value_truthy(const struct JlispValue *v);
+
+// Store the integer in `v` in `out`, false if it isn't one that fits
+//
+// # Safety
+// `v` is a live value and `out` writable
+bool jlisp_value_int(const struct JlispValue *v, int64_t *out);
+
+// Store the number in `v` as a double in `out`, false if it isn't a number
+//
+// # Safety
+// `v` is a live value and `out` writable
+bool jlisp_value_float(const struct JlispValue *v, double *out);
+
+// Store the code point of the Char in `v` in `out`, false if it isn't one
+//
+// # Safety
+// `v` is a live value and `out` writable
+bool jlisp_value_char(const struct JlispValue *v, uint32_t *out);
+
+// The numerator of a Rational, or an integer itself, as a Number value.
+// NULL for other values.
+//
+// # Safety
+// `v` is a live value
+struct JlispValue *jlisp_value_numerator(const struct JlispValue *v);
+
+// The denominator of a Rational, 1 for an integer, as a Number value.
+// NULL for other values.
+//
+// # Safety
+// `v` is a live value
+struct JlispValue *jlisp_value_denominator(const struct JlispValue *v);
+
+// The text of a String or the name of a Symbol, NULL for other values.
+// It's freed with `v`.
+//
+// # Safety
+// `v` is a live value
+const char *jlisp_value_string(const struct JlispValue *v);
+
+// `v` printed the way the REPL prints it, freed with `jlisp_string_free`
+//
+// # Safety
+// `v` is a live value
+char *jlisp_value_display(const struct JlispValue *v);
+
+// # Safety
+// `s` is NULL or from `jlisp_value_display`, and isn't used after
+void jlisp_string_free(char *s);
+
+// The number of elements of a List, 0 for other values
+//
+// # Safety
+// `v` is a live value
+size_t jlisp_list_len(const struct JlispValue *v);
+
+// Element `i` of a List, NULL if `v` isn't one or `i` is out of range
+//
+// # Safety
+// `v` is a live value
+struct JlispValue *jlisp_list_get(const struct JlispValue *v, size_t i);
+
+// The number of entries of a Map, 0 for other values
+//
+// # Safety
+// `v` is a live value
+size_t jlisp_map_len(const struct JlispValue *v);
+
+// The key of entry `i` of a Map, in the order the keys were added. NULL if
+// `v` isn't one or `i` is out of range.
+//
+// # Safety
+// `v` is a live value
+struct JlispValue *jlisp_map_key(const struct JlispValue *v, size_t i);
+
+// The value of entry `i` of a Map, NULL if `v` isn't one or `i` is out of
+// range
+//
+// # Safety
+// `v` is a live value
+struct JlispValue *jlisp_map_value(const struct JlispValue *v, size_t i);
+
+// The number of members of a Set, 0 for other values
+//
+// # Safety
+// `v` is a live value
+size_t jlisp_set_len(const struct JlispValue *v);
+
+// The members of a Set as a List, in no particular order, to go through
+// with `jlisp_list_get`. NULL for other values.
+//
+// # Safety
+// `v` is a live value
+struct JlispValue *jlisp_set_items(const struct JlispValue *v);
+
+#endif  /* JLISP_H */
@@ -0,0 +1,666 @@
+//! A C API for embedding jlisp, see `include/jlisp.h`.
+//!
+//! An interpreter is a `JlispInterp`, every value handed to C is a
+//! `JlispValue` it owns and frees with `jlisp_value_free`, unless the docs of
+//! the function say it's borrowed. Strings go in and out as NUL-terminated
+//! UTF-8. When a call fails it returns NULL or false and the interpreter keeps
+//! the error until its next call, for `jlisp_error_message` and
+//! `jlisp_error_kind`. A panic doesn't unwind into C, the call fails with an
+//! error of kind "panic" instead. Like `Interpreter`, a `JlispInterp` can be
+//! handed to another thread, as long as only one thread uses it at a time.
+
+use std::any::Any;
+use std::cell::{OnceCell, RefCell};
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use jlisp::ast::{Error, Expr};
+use jlisp::builtin::Arity;
+use jlisp::convert::{FromExpr, IntoExpr};
+use jlisp::interpreter::Interpreter;
+use jlisp::span::Span;
+
+pub struct JlispInterp {
+    interp: Interpreter,
+    // message and kind of the error of the last call, if it failed. Only
+    // ever borrowed shared, a callback may call back into the interpreter.
+    error: RefCell<Option<(CString, CString)>>,
+}
+
+pub struct JlispValue {
+    expr: Expr,
+    // set by `jlisp_raise`, a callback returning this value raises it
+    raise: Option<String>,
+    // what `jlisp_value_string` hands out, freed with the value
+    text: OnceCell<Option<CString>>,
+}
+
+/// What a `JlispValue` holds
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JlispType {
+    Nil,
+    Bool,
+    /// An integer, `jlisp_value_int` fails for the ones beyond 64 bits
+    Number,
+    Rational,
+    Float,
+    Char,
+    String,
+    Symbol,
+    List,
+    Map,
+    Set,
+    /// A builtin, lambda or macro
+    Function,
+    Other,
+}
+
+/// A builtin implemented in C. `args` are borrowed for the call, the value
+/// returned is taken over by the interpreter, a `jlisp_raise` value raises
+/// an error and NULL raises a generic one. It may call back into the
+/// interpreter running it, but not free it.
+pub type JlispFn = unsafe extern "C" fn(
+    userdata: *mut c_void,
+    args: *const *const JlispValue,
+    nargs: usize,
+) -> *mut JlispValue;
+
+// the `userdata` of a C builtin, only ever handed back to its callback
+struct Userdata(*mut c_void);
+
+// SAFETY: the callback gets it on whatever thread runs the interpreter,
+// `jlisp_register` leaves it to the caller that this is fine
+unsafe impl Send for Userdata {}
+unsafe impl Sync for Userdata {}
+
+impl Userdata {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+fn value(expr: Expr) -> *mut JlispValue {
+    Box::into_raw(Box::new(JlispValue {
+        expr,
+        raise: None,
+        text: OnceCell::new(),
+    }))
+}
+
+// `s` as a `&str`, `None` for NULL or text that isn't UTF-8
+unsafe fn text<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(s) }.to_str().ok()
+}
+
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "\\0")).unwrap()
+}
+
+// `f()`, or `fallback` if it panics, a panic mustn't unwind into C
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        "unknown panic"
+    }
+}
+
+impl JlispInterp {
+    fn finish(&self, result: Result<Expr, Error>) -> *mut JlispValue {
+        match result {
+            Ok(expr) => {
+                self.error.replace(None);
+                value(expr)
+            }
+            Err(err) => {
+                let error = (c_string(&err.message()), c_string(err.kind()));
+                self.error.replace(Some(error));
+                ptr::null_mut()
+            }
+        }
+    }
+
+    fn fail(&self, msg: &str) -> *mut JlispValue {
+        self.error.replace(Some((c_string(msg), c_string("type"))));
+        ptr::null_mut()
+    }
+
+    // like `guard`, keeping the panic as the error of the call
+    fn guard<T>(&self, fallback: T, f: impl FnOnce() -> T) -> T {
+        panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+            let msg = format!("panic: {}", panic_message(&*payload));
+            self.error
+                .replace(Some((c_string(&msg), c_string("panic"))));
+            fallback
+        })
+    }
+}
+
+/// A new interpreter with the builtins and the prelude
+#[unsafe(no_mangle)]
+pub extern "C" fn jlisp_new() -> *mut JlispInterp {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(JlispInterp {
+            interp: Interpreter::new(),
+            error: RefCell::new(None),
+        }))
+    })
+}
+
+/// A new interpreter with only the builtins
+#[unsafe(no_mangle)]
+pub extern "C" fn jlisp_new_bare() -> *mut JlispInterp {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(JlispInterp {
+            interp: Interpreter::bare(),
+            error: RefCell::new(None),
+        }))
+    })
+}
+
+/// # Safety
+/// `interp` is NULL or from `jlisp_new`, and isn't used after
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_free(interp: *mut JlispInterp) {
+    guard((), || {
+        if !interp.is_null() {
+            drop(unsafe { Box::from_raw(interp) });
+        }
+    })
+}
+
+/// Every form in `src` evaluated in order, the value of the last, or NULL
+/// if one fails
+///
+/// # Safety
+/// `interp` is from `jlisp_new`, `src` a NUL-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_eval(
+    interp: *mut JlispInterp,
+    src: *const c_char,
+) -> *mut JlispValue {
+    let interp = unsafe { &*interp };
+    interp.guard(ptr::null_mut(), || {
+        let Some(src) = (unsafe { text(src) }) else {
+            return interp.fail("jlisp_eval: source isn't UTF-8");
+        };
+        let result = interp.interp.eval_str(src);
+        interp.finish(result)
+    })
+}
+
+/// The global `name` called with the `nargs` values in `args`, which stay
+/// the caller's, or NULL if the call fails
+///
+/// # Safety
+/// `interp` is from `jlisp_new`, `name` a NUL-terminated string and `args`
+/// points at `nargs` values
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_call(
+    interp: *mut JlispInterp,
+    name: *const c_char,
+    args: *const *const JlispValue,
+    nargs: usize,
+) -> *mut JlispValue {
+    let interp = unsafe { &*interp };
+    interp.guard(ptr::null_mut(), || {
+        let Some(name) = (unsafe { text(name) }) else {
+            return interp.fail("jlisp_call: name isn't UTF-8");
+        };
+        let args = unsafe { borrow_args(args, nargs) };
+        let result = interp.interp.call::<_, Expr>(name, args);
+        interp.finish(result)
+    })
+}
+
+unsafe fn borrow_args(args: *const *const JlispValue, nargs: usize) -> Vec<Expr> {
+    if nargs == 0 {
+        return Vec::new();
+    }
+    let args = unsafe { std::slice::from_raw_parts(args, nargs) };
+    args.iter().map(|&v| unsafe { &*v }.expr.clone()).collect()
+}
+
+/// The value of the global `name`, or NULL if it isn't bound
+///
+/// # Safety
+/// `interp` is from `jlisp_new`, `name` a NUL-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_get_global(
+    interp: *mut JlispInterp,
+    name: *const c_char,
+) -> *mut JlispValue {
+    let interp = unsafe { &*interp };
+    interp.guard(ptr::null_mut(), || {
+        match unsafe { text(name) }.and_then(|name| interp.interp.get_global(name)) {
+            Some(expr) => value(expr),
+            None => ptr::null_mut(),
+        }
+    })
+}
+
+/// Bind the global `name` to `value`, which stays the caller's
+///
+/// # Safety
+/// `interp` is from `jlisp_new`, `name` a NUL-terminated string and `value`
+/// a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_set_global(
+    interp: *mut JlispInterp,
+    name: *const c_char,
+    value: *const JlispValue,
+) -> bool {
+    let interp = unsafe { &*interp };
+    interp.guard(false, || {
+        let Some(name) = (unsafe { text(name) }) else {
+            return false;
+        };
+        interp
+            .interp
+            .set_global(name, unsafe { &*value }.expr.clone());
+        true
+    })
+}
+
+/// Bind `name` to a builtin calling `f` with `userdata`. It takes
+/// `min_args` arguments, or at least that many if `variadic`.
+///
+/// # Safety
+/// `interp` is from `jlisp_new`, `name` a NUL-terminated string and
+/// `userdata` whatever `f` expects, for as long as the interpreter lives and
+/// on any thread it is used from
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_register(
+    interp: *mut JlispInterp,
+    name: *const c_char,
+    min_args: usize,
+    variadic: bool,
+    f: JlispFn,
+    userdata: *mut c_void,
+) -> bool {
+    let interp = unsafe { &*interp };
+    interp.guard(false, || {
+        let Some(name) = (unsafe { text(name) }) else {
+            return false;
+        };
+        let arity = if variadic {
+            Arity::AtLeast(min_args)
+        } else {
+            Arity::Exact(min_args)
+        };
+        let func = name.to_string();
+        let userdata = Userdata(userdata);
+        interp.interp.register(name, arity, &[], move |args, span| {
+            let args: Vec<*mut JlispValue> = args.into_iter().map(value).collect();
+            let out = unsafe {
+                f(
+                    userdata.get(),
+                    args.as_ptr() as *const *const JlispValue,
+                    args.len(),
+                )
+            };
+            for arg in args {
+                drop(unsafe { Box::from_raw(arg) });
+            }
+            if out.is_null() {
+                return Err(Error::User {
+                    msg: format!("{func} failed"),
+                    payload: None,
+                    span,
+                });
+            }
+            let out = unsafe { Box::from_raw(out) };
+            match out.raise {
+                Some(msg) => Err(Error::User {
+                    msg,
+                    payload: None,
+                    span,
+                }),
+                None => Ok(out.expr),
+            }
+        });
+        true
+    })
+}
+
+/// The message of the error the last call on `interp` failed with, NULL if
+/// it didn't fail. It's valid until the next call.
+///
+/// # Safety
+/// `interp` is from `jlisp_new`
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_error_message(interp: *const JlispInterp) -> *const c_char {
+    guard(ptr::null(), || match &*unsafe { &*interp }.error.borrow() {
+        Some((msg, _)) => msg.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// The kind of that error, as `(get e "kind")` in a `catch` gives it
+///
+/// # Safety
+/// `interp` is from `jlisp_new`
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_error_kind(interp: *const JlispInterp) -> *const c_char {
+    guard(ptr::null(), || match &*unsafe { &*interp }.error.borrow() {
+        Some((_, kind)) => kind.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jlisp_nil() -> *mut JlispValue {
+    guard(ptr::null_mut(), || value(Expr::Nil))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jlisp_bool(b: bool) -> *mut JlispValue {
+    guard(ptr::null_mut(), || value(Expr::Bool(b)))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jlisp_int(n: i64) -> *mut JlispValue {
+    guard(ptr::null_mut(), || value(Expr::Number(n)))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn jlisp_float(f: f64) -> *mut JlispValue {
+    guard(ptr::null_mut(), || value(Expr::Float(f)))
+}
+
+/// A String value, NULL if `s` isn't UTF-8
+///
+/// # Safety
+/// `s` is a NUL-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_string(s: *const c_char) -> *mut JlispValue {
+    guard(ptr::null_mut(), || match unsafe { text(s) } {
+        Some(s) => value(Expr::String(s.to_string())),
+        None => ptr::null_mut(),
+    })
+}
+
+/// A List of the `n` values in `items`, which stay the caller's
+///
+/// # Safety
+/// `items` points at `n` values
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_list(items: *const *const JlispValue, n: usize) -> *mut JlispValue {
+    guard(ptr::null_mut(), || {
+        value(Expr::List(unsafe { borrow_args(items, n) }.into()))
+    })
+}
+
+/// A value that, returned from a callback, raises an error with `msg`
+///
+/// # Safety
+/// `msg` is a NUL-terminated string
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_raise(msg: *const c_char) -> *mut JlispValue {
+    guard(ptr::null_mut(), || {
+        let msg = unsafe { text(msg) }.unwrap_or("error in callback");
+        Box::into_raw(Box::new(JlispValue {
+            expr: Expr::Nil,
+            raise: Some(msg.to_string()),
+            text: OnceCell::new(),
+        }))
+    })
+}
+
+/// # Safety
+/// `v` is NULL or a value the caller owns, and isn't used after
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_free(v: *mut JlispValue) {
+    guard((), || {
+        if !v.is_null() {
+            drop(unsafe { Box::from_raw(v) });
+        }
+    })
+}
+
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_type(v: *const JlispValue) -> JlispType {
+    guard(JlispType::Other, || match unsafe { &*v }.expr {
+        Expr::Nil => JlispType::Nil,
+        Expr::Bool(_) => JlispType::Bool,
+        Expr::Number(_) | Expr::BigInt(_) => JlispType::Number,
+        Expr::Rational(_) => JlispType::Rational,
+        Expr::Float(_) => JlispType::Float,
+        Expr::Char(_) => JlispType::Char,
+        Expr::String(_) => JlispType::String,
+        Expr::Symbol(_) => JlispType::Symbol,
+        Expr::List(_) | Expr::Sexpr(_) => JlispType::List,
+        Expr::Map(_) => JlispType::Map,
+        Expr::Set(_) => JlispType::Set,
+        Expr::Builtin(_) | Expr::Lambda { .. } | Expr::Macro(_) => JlispType::Function,
+        Expr::Comment(_) | Expr::Regex(_) => JlispType::Other,
+    })
+}
+
+/// Whether `v` is true as a condition, see `Expr::is_truthy`
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_truthy(v: *const JlispValue) -> bool {
+    guard(false, || unsafe { &*v }.expr.is_truthy())
+}
+
+/// Store the integer in `v` in `out`, false if it isn't one that fits
+///
+/// # Safety
+/// `v` is a live value and `out` writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_int(v: *const JlispValue, out: *mut i64) -> bool {
+    guard(false, || match unsafe { &*v }.expr {
+        Expr::Number(n) => {
+            unsafe { *out = n };
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Store the number in `v` as a double in `out`, false if it isn't a number
+///
+/// # Safety
+/// `v` is a live value and `out` writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_float(v: *const JlispValue, out: *mut f64) -> bool {
+    guard(false, || {
+        let expr = unsafe { &*v }.expr.clone();
+        match f64::from_expr(expr, "jlisp_value_float", Span::default()) {
+            Ok(f) => {
+                unsafe { *out = f };
+                true
+            }
+            Err(_) => false,
+        }
+    })
+}
+
+/// Store the code point of the Char in `v` in `out`, false if it isn't one
+///
+/// # Safety
+/// `v` is a live value and `out` writable
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_char(v: *const JlispValue, out: *mut u32) -> bool {
+    guard(false, || match unsafe { &*v }.expr {
+        Expr::Char(c) => {
+            unsafe { *out = u32::from(c) };
+            true
+        }
+        _ => false,
+    })
+}
+
+/// The numerator of a Rational, or an integer itself, as a Number value.
+/// NULL for other values.
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_numerator(v: *const JlispValue) -> *mut JlispValue {
+    guard(ptr::null_mut(), || match &unsafe { &*v }.expr {
+        Expr::Rational(r) => value(r.numer().clone().into_expr()),
+        n @ (Expr::Number(_) | Expr::BigInt(_)) => value(n.clone()),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// The denominator of a Rational, 1 for an integer, as a Number value.
+/// NULL for other values.
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_denominator(v: *const JlispValue) -> *mut JlispValue {
+    guard(ptr::null_mut(), || match &unsafe { &*v }.expr {
+        Expr::Rational(r) => value(r.denom().clone().into_expr()),
+        Expr::Number(_) | Expr::BigInt(_) => value(Expr::Number(1)),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// The text of a String or the name of a Symbol, NULL for other values.
+/// It's freed with `v`.
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_string(v: *const JlispValue) -> *const c_char {
+    guard(ptr::null(), || {
+        let v = unsafe { &*v };
+        let text = v.text.get_or_init(|| match &v.expr {
+            Expr::String(s) | Expr::Symbol(s) => Some(c_string(s)),
+            _ => None,
+        });
+        text.as_ref().map_or(ptr::null(), |s| s.as_ptr())
+    })
+}
+
+/// `v` printed the way the REPL prints it, freed with `jlisp_string_free`
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_value_display(v: *const JlispValue) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        c_string(&unsafe { &*v }.expr.to_string()).into_raw()
+    })
+}
+
+/// # Safety
+/// `s` is NULL or from `jlisp_value_display`, and isn't used after
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_string_free(s: *mut c_char) {
+    guard((), || {
+        if !s.is_null() {
+            drop(unsafe { CString::from_raw(s) });
+        }
+    })
+}
+
+/// The number of elements of a List, 0 for other values
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_list_len(v: *const JlispValue) -> usize {
+    guard(0, || match &unsafe { &*v }.expr {
+        Expr::List(items) | Expr::Sexpr(items) => items.len(),
+        _ => 0,
+    })
+}
+
+/// Element `i` of a List, NULL if `v` isn't one or `i` is out of range
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_list_get(v: *const JlispValue, i: usize) -> *mut JlispValue {
+    guard(ptr::null_mut(), || match &unsafe { &*v }.expr {
+        Expr::List(items) | Expr::Sexpr(items) if i < items.len() => value(items[i].clone()),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// The number of entries of a Map, 0 for other values
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_map_len(v: *const JlispValue) -> usize {
+    guard(0, || match &unsafe { &*v }.expr {
+        Expr::Map(map) => map.len(),
+        _ => 0,
+    })
+}
+
+/// The key of entry `i` of a Map, in the order the keys were added. NULL if
+/// `v` isn't one or `i` is out of range.
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_map_key(v: *const JlispValue, i: usize) -> *mut JlispValue {
+    guard(ptr::null_mut(), || match &unsafe { &*v }.expr {
+        Expr::Map(map) => map
+            .get_index(i)
+            .map_or(ptr::null_mut(), |(k, _)| value(k.clone())),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// The value of entry `i` of a Map, NULL if `v` isn't one or `i` is out of
+/// range
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_map_value(v: *const JlispValue, i: usize) -> *mut JlispValue {
+    guard(ptr::null_mut(), || match &unsafe { &*v }.expr {
+        Expr::Map(map) => map
+            .get_index(i)
+            .map_or(ptr::null_mut(), |(_, v)| value(v.clone())),
+        _ => ptr::null_mut(),
+    })
+}
+
+/// The number of members of a Set, 0 for other values
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_set_len(v: *const JlispValue) -> usize {
+    guard(0, || match &unsafe { &*v }.expr {
+        Expr::Set(set) => set.len(),
+        _ => 0,
+    })
+}
+
+/// The members of a Set as a List, in no particular order, to go through
+/// with `jlisp_list_get`. NULL for other values.
+///
+/// # Safety
+/// `v` is a live value
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jlisp_set_items(v: *const JlispValue) -> *mut JlispValue {
+    guard(ptr::null_mut(), || match &unsafe { &*v }.expr {
+        Expr::Set(set) => value(Expr::List(set.iter().cloned().collect())),
+        _ => ptr::null_mut(),
+    })
+}
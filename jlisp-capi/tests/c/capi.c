@@ -0,0 +1,287 @@
+// Exercises the C API, built and run by tests/capi.rs against libjlisp_capi.a.
+// Prints "ok" and exits 0 when every check passes.
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "jlisp.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                     \
+    do {                                                                \
+        if (!(cond)) {                                                  \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,      \
+                    __LINE__, #cond);                                   \
+            failures++;                                                 \
+        }                                                               \
+    } while (0)
+
+// (c-sum n ...) adds up its arguments, counting calls in userdata
+static JlispValue *c_sum(void *userdata, const JlispValue *const *args, size_t nargs) {
+    int *calls = userdata;
+    (*calls)++;
+    int64_t total = 0;
+    for (size_t i = 0; i < nargs; i++) {
+        int64_t n;
+        if (!jlisp_value_int(args[i], &n)) {
+            return jlisp_raise("c-sum: expected integers");
+        }
+        total += n;
+    }
+    return jlisp_int(total);
+}
+
+static JlispValue *c_fail(void *userdata, const JlispValue *const *args, size_t nargs) {
+    (void)userdata;
+    (void)args;
+    (void)nargs;
+    return NULL;
+}
+
+// (c-nested x) evaluates more code in the interpreter that called it
+static JlispValue *c_nested(void *userdata, const JlispValue *const *args, size_t nargs) {
+    (void)args;
+    (void)nargs;
+    JlispInterp *interp = userdata;
+    CHECK(jlisp_eval(interp, "(undefined)") == NULL);
+    return jlisp_eval(interp, "(+ 40 2)");
+}
+
+static void test_eval(JlispInterp *interp) {
+    JlispValue *v = jlisp_eval(interp, "(def [x] 20) (+ x 22)");
+    CHECK(v != NULL);
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_NUMBER);
+    int64_t n = 0;
+    CHECK(jlisp_value_int(v, &n) && n == 42);
+    CHECK(jlisp_error_message(interp) == NULL);
+    jlisp_value_free(v);
+
+    v = jlisp_eval(interp, "(/ 1.0 4)");
+    double f = 0;
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_FLOAT);
+    CHECK(jlisp_value_float(v, &f) && f == 0.25);
+    CHECK(!jlisp_value_int(v, &n));
+    jlisp_value_free(v);
+
+    v = jlisp_eval(interp, "(str-sub \"hello\" 1 3)");
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_STRING);
+    CHECK(strcmp(jlisp_value_string(v), "el") == 0);
+    jlisp_value_free(v);
+}
+
+static void test_values(JlispInterp *interp) {
+    JlispValue *v = jlisp_eval(interp, "[1 \"two\" 'c' nil]");
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_LIST);
+    CHECK(jlisp_list_len(v) == 4);
+    JlispValue *second = jlisp_list_get(v, 1);
+    CHECK(strcmp(jlisp_value_string(second), "two") == 0);
+    jlisp_value_free(second);
+    JlispValue *last = jlisp_list_get(v, 3);
+    CHECK(jlisp_value_type(last) == JLISP_TYPE_NIL);
+    CHECK(!jlisp_value_truthy(last));
+    jlisp_value_free(last);
+    CHECK(jlisp_list_get(v, 4) == NULL);
+    char *text = jlisp_value_display(v);
+    CHECK(strcmp(text, "[1 \"two\" 'c' nil]") == 0);
+    jlisp_string_free(text);
+    jlisp_value_free(v);
+
+    JlispValue *items[] = {jlisp_int(1), jlisp_string("a"), jlisp_bool(true)};
+    JlispValue *list = jlisp_list((const JlispValue *const *)items, 3);
+    CHECK(jlisp_set_global(interp, "from-c", list));
+    v = jlisp_eval(interp, "(len from-c)");
+    int64_t n = 0;
+    CHECK(jlisp_value_int(v, &n) && n == 3);
+    jlisp_value_free(v);
+    for (int i = 0; i < 3; i++) {
+        jlisp_value_free(items[i]);
+    }
+    jlisp_value_free(list);
+
+    v = jlisp_get_global(interp, "from-c");
+    CHECK(v != NULL && jlisp_list_len(v) == 3);
+    jlisp_value_free(v);
+    CHECK(jlisp_get_global(interp, "not-bound") == NULL);
+
+    v = jlisp_eval(interp, "+");
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_FUNCTION);
+    jlisp_value_free(v);
+
+    uint32_t c = 0;
+    v = jlisp_eval(interp, "(int->char 233)");
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_CHAR);
+    CHECK(jlisp_value_char(v, &c) && c == 0xe9);
+    CHECK(!jlisp_value_int(v, &n));
+    jlisp_value_free(v);
+    v = jlisp_int(1);
+    CHECK(!jlisp_value_char(v, &c));
+    jlisp_value_free(v);
+}
+
+static void test_rationals(JlispInterp *interp) {
+    JlispValue *v = jlisp_eval(interp, "(/ -6 4)");
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_RATIONAL);
+    JlispValue *num = jlisp_value_numerator(v);
+    JlispValue *den = jlisp_value_denominator(v);
+    int64_t n = 0;
+    CHECK(jlisp_value_int(num, &n) && n == -3);
+    CHECK(jlisp_value_int(den, &n) && n == 2);
+    jlisp_value_free(num);
+    jlisp_value_free(den);
+    jlisp_value_free(v);
+
+    // parts beyond 64 bits are still values, just not ones that fit an int
+    v = jlisp_eval(interp, "(/ 1 (** 2 70))");
+    den = jlisp_value_denominator(v);
+    CHECK(jlisp_value_type(den) == JLISP_TYPE_NUMBER && !jlisp_value_int(den, &n));
+    char *text = jlisp_value_display(den);
+    CHECK(strcmp(text, "1180591620717411303424") == 0);
+    jlisp_string_free(text);
+    jlisp_value_free(den);
+    jlisp_value_free(v);
+
+    v = jlisp_int(7);
+    num = jlisp_value_numerator(v);
+    den = jlisp_value_denominator(v);
+    CHECK(jlisp_value_int(num, &n) && n == 7);
+    CHECK(jlisp_value_int(den, &n) && n == 1);
+    jlisp_value_free(num);
+    jlisp_value_free(den);
+    jlisp_value_free(v);
+
+    v = jlisp_float(0.5);
+    CHECK(jlisp_value_numerator(v) == NULL && jlisp_value_denominator(v) == NULL);
+    jlisp_value_free(v);
+}
+
+static void test_collections(JlispInterp *interp) {
+    JlispValue *v = jlisp_eval(interp, "{\"a\" 1 \"b\" 2}");
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_MAP);
+    CHECK(jlisp_map_len(v) == 2);
+    int64_t n = 0;
+    for (size_t i = 0; i < jlisp_map_len(v); i++) {
+        JlispValue *key = jlisp_map_key(v, i);
+        JlispValue *val = jlisp_map_value(v, i);
+        CHECK(strcmp(jlisp_value_string(key), i == 0 ? "a" : "b") == 0);
+        CHECK(jlisp_value_int(val, &n) && n == (int64_t)i + 1);
+        jlisp_value_free(key);
+        jlisp_value_free(val);
+    }
+    CHECK(jlisp_map_key(v, 2) == NULL && jlisp_map_value(v, 2) == NULL);
+    CHECK(jlisp_set_len(v) == 0 && jlisp_set_items(v) == NULL);
+    jlisp_value_free(v);
+
+    v = jlisp_eval(interp, "(set [1 2 3 2])");
+    CHECK(jlisp_value_type(v) == JLISP_TYPE_SET);
+    CHECK(jlisp_set_len(v) == 3);
+    JlispValue *items = jlisp_set_items(v);
+    CHECK(jlisp_list_len(items) == 3);
+    int64_t sum = 0;
+    for (size_t i = 0; i < jlisp_list_len(items); i++) {
+        JlispValue *item = jlisp_list_get(items, i);
+        CHECK(jlisp_value_int(item, &n));
+        sum += n;
+        jlisp_value_free(item);
+    }
+    CHECK(sum == 6);
+    CHECK(jlisp_map_len(v) == 0 && jlisp_map_key(v, 0) == NULL);
+    jlisp_value_free(items);
+    jlisp_value_free(v);
+}
+
+static void test_call(JlispInterp *interp) {
+    jlisp_value_free(jlisp_eval(interp, "(fun [twice f x] [f (f x)])"));
+    jlisp_value_free(jlisp_eval(interp, "(fun [inc n] [+ n 1])"));
+    JlispValue *inc = jlisp_get_global(interp, "inc");
+    JlispValue *five = jlisp_int(5);
+    const JlispValue *args[] = {inc, five};
+    JlispValue *v = jlisp_call(interp, "twice", args, 2);
+    int64_t n = 0;
+    CHECK(jlisp_value_int(v, &n) && n == 7);
+    jlisp_value_free(v);
+    jlisp_value_free(inc);
+    jlisp_value_free(five);
+
+    CHECK(jlisp_call(interp, "no-such-fn", NULL, 0) == NULL);
+    CHECK(strcmp(jlisp_error_kind(interp), "undefined-symbol") == 0);
+}
+
+static void test_callbacks(JlispInterp *interp) {
+    int calls = 0;
+    CHECK(jlisp_register(interp, "c-sum", 1, true, c_sum, &calls));
+    CHECK(jlisp_register(interp, "c-fail", 0, false, c_fail, NULL));
+
+    JlispValue *v = jlisp_eval(interp, "(c-sum 1 2 3 4)");
+    int64_t n = 0;
+    CHECK(jlisp_value_int(v, &n) && n == 10);
+    jlisp_value_free(v);
+    v = jlisp_eval(interp, "(map (\\ [x] [c-sum x x]) [1 2 3])");
+    char *text = jlisp_value_display(v);
+    CHECK(strcmp(text, "[2 4 6]") == 0);
+    jlisp_string_free(text);
+    jlisp_value_free(v);
+    CHECK(calls == 4);
+
+    CHECK(jlisp_eval(interp, "(c-sum 1 \"two\")") == NULL);
+    CHECK(strcmp(jlisp_error_message(interp), "c-sum: expected integers") == 0);
+    CHECK(strcmp(jlisp_error_kind(interp), "user") == 0);
+
+    CHECK(jlisp_eval(interp, "(c-fail 1)") == NULL);
+    CHECK(strcmp(jlisp_error_kind(interp), "arity") == 0);
+
+    v = jlisp_eval(interp, "(try (c-fail) (catch e (get e \"message\")))");
+    CHECK(strcmp(jlisp_value_string(v), "c-fail failed") == 0);
+    jlisp_value_free(v);
+
+    // callbacks can call back into the interpreter running them
+    CHECK(jlisp_register(interp, "c-nested", 1, false, c_nested, interp));
+    v = jlisp_eval(interp, "(+ (c-nested 0) 1)");
+    CHECK(jlisp_value_int(v, &n) && n == 43);
+    CHECK(jlisp_error_message(interp) == NULL);
+    jlisp_value_free(v);
+}
+
+static void test_errors(JlispInterp *interp) {
+    CHECK(jlisp_eval(interp, "(/ 1 0)") == NULL);
+    CHECK(strcmp(jlisp_error_kind(interp), "division-by-zero") == 0);
+    CHECK(jlisp_error_message(interp) != NULL);
+
+    CHECK(jlisp_eval(interp, "(error \"boom\")") == NULL);
+    CHECK(strcmp(jlisp_error_message(interp), "boom") == 0);
+
+    CHECK(jlisp_eval(interp, "(+ 1") == NULL);
+    CHECK(strcmp(jlisp_error_kind(interp), "parse") == 0);
+
+    // a call that succeeds clears the error
+    jlisp_value_free(jlisp_eval(interp, "1"));
+    CHECK(jlisp_error_message(interp) == NULL);
+}
+
+int main(void) {
+    JlispInterp *interp = jlisp_new();
+    test_eval(interp);
+    test_values(interp);
+    test_rationals(interp);
+    test_collections(interp);
+    test_call(interp);
+    test_callbacks(interp);
+    test_errors(interp);
+    jlisp_free(interp);
+
+    // interpreters don't see each other's globals
+    JlispInterp *a = jlisp_new_bare();
+    JlispInterp *b = jlisp_new_bare();
+    jlisp_value_free(jlisp_eval(a, "(def [only-a] 1)"));
+    CHECK(jlisp_eval(b, "only-a") == NULL);
+    jlisp_free(a);
+    jlisp_free(b);
+
+    if (failures) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    printf("ok\n");
+    return 0;
+}
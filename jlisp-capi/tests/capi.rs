@@ -0,0 +1,44 @@
+// Other targets link the static library differently, so this only runs
+// where the system libraries it needs are known
+#![cfg(any(target_os = "linux", target_os = "macos"))]
+
+use std::path::PathBuf;
+use std::process::Command;
+
+// where cargo puts libjlisp_capi.a, next to the deps/ dir this test runs from
+fn lib_dir() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    exe.parent().unwrap().parent().unwrap().to_path_buf()
+}
+
+// the system libraries the Rust std in libjlisp_capi.a needs, as
+// `rustc --print native-static-libs` lists them
+#[cfg(target_os = "linux")]
+const SYSTEM_LIBS: &[&str] = &["-lpthread", "-ldl", "-lm"];
+#[cfg(target_os = "macos")]
+const SYSTEM_LIBS: &[&str] = &["-lSystem", "-lc", "-lm"];
+
+#[test]
+fn c_program_links_and_runs() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("capi");
+    let lib = lib_dir().join("libjlisp_capi.a");
+    assert!(lib.exists(), "{} wasn't built", lib.display());
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&compiler)
+        .arg(format!("{root}/tests/c/capi.c"))
+        .arg(format!("-I{root}/include"))
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&out)
+        .arg(&lib)
+        .args(SYSTEM_LIBS)
+        .status()
+        .unwrap_or_else(|e| panic!("couldn't run {compiler}: {e}"));
+    assert!(status.success(), "compiling tests/c/capi.c failed");
+
+    let run = Command::new(&out).output().unwrap();
+    let stderr = String::from_utf8_lossy(&run.stderr);
+    assert!(run.status.success(), "tests/c/capi.c failed:\n{stderr}");
+    assert_eq!(String::from_utf8_lossy(&run.stdout), "ok\n");
+}
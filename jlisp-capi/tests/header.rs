@@ -0,0 +1,25 @@
+use std::fs;
+
+// include/jlisp.h is checked in, so it has to match what cbindgen makes of
+// src/lib.rs now. UPDATE_HEADER=1 rewrites it instead.
+#[test]
+fn header_is_up_to_date() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let config = cbindgen::Config::from_file(format!("{root}/cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::generate_with_config(root, config)
+        .expect("couldn't generate the C header")
+        .write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let path = format!("{root}/include/jlisp.h");
+    if std::env::var_os("UPDATE_HEADER").is_some() {
+        fs::write(&path, &generated).unwrap();
+    }
+    let checked_in = fs::read_to_string(&path).unwrap();
+    assert!(
+        checked_in == generated,
+        "include/jlisp.h is out of date, regenerate it with \
+         UPDATE_HEADER=1 cargo test -p jlisp-capi --test header"
+    );
+}
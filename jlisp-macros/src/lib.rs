@@ -0,0 +1,148 @@
+//! `#[jlisp::builtin]`, declares a jlisp builtin with a plain Rust function.
+//!
+//! ```ignore
+//! #[jlisp::builtin(name = "str-sub")]
+//! fn str_sub(s: String, start: i64, end: i64, span: Span) -> Result<String, Error> {
+//!     ...
+//! }
+//! ```
+//!
+//! Every argument is converted with `FromExpr`, so a call with the wrong
+//! number of arguments or one of the wrong type fails before the function
+//! runs, and the value it returns is converted back with `IntoExpr`. A
+//! `Span` parameter gets the span of the call and a `&Env` one the env it was
+//! called in, neither of them counts as an argument. A last parameter marked
+//! `#[rest]` takes the remaining arguments as a `Vec`. The builtin is
+//! registered in every env `setup_builtins` makes, under `name` or the
+//! function's name with `-` for `_`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{FnArg, ItemFn, LitStr, Pat, ReturnType, Type, parse_macro_input};
+
+#[proc_macro_attribute]
+pub fn builtin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut name: Option<LitStr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `name = \"...\"`"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+    let mut func = parse_macro_input!(item as ItemFn);
+    match expand(name, &mut func) {
+        Ok(registration) => quote!(#func #registration).into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+// the last segment of a path type, `Span` for `crate::span::Span`
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn is_env(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(r) if type_name(&r.elem).as_deref() == Some("Env"))
+}
+
+fn expand(name: Option<LitStr>, func: &mut ItemFn) -> syn::Result<TokenStream2> {
+    let ident = func.sig.ident.clone();
+    let name = name.map_or_else(|| ident.to_string().replace('_', "-"), |n| n.value());
+
+    let mut bindings = Vec::new();
+    let mut call_args = Vec::new();
+    let mut required = 0usize;
+    let mut rest = false;
+    let inputs = func.sig.inputs.len();
+    for (i, input) in func.sig.inputs.iter_mut().enumerate() {
+        let FnArg::Typed(param) = input else {
+            return Err(syn::Error::new(input.span(), "builtins can't take self"));
+        };
+        let var = match &*param.pat {
+            Pat::Ident(pat) => format_ident!("__{}", pat.ident),
+            _ => format_ident!("__arg{}", i),
+        };
+        let ty = &param.ty;
+        let is_rest = param.attrs.iter().any(|a| a.path().is_ident("rest"));
+        param.attrs.retain(|a| !a.path().is_ident("rest"));
+
+        if type_name(ty).as_deref() == Some("Span") {
+            call_args.push(quote!(span));
+        } else if is_env(ty) {
+            call_args.push(quote!(env));
+        } else if is_rest {
+            if i + 1 != inputs {
+                return Err(syn::Error::new(
+                    param.span(),
+                    "#[rest] must be the last parameter",
+                ));
+            }
+            rest = true;
+            bindings.push(quote! {
+                let #var = <#ty as ::jlisp::convert::FromExpr>::from_expr(
+                    ::jlisp::ast::Expr::List(args.by_ref().collect()),
+                    name,
+                    span,
+                )?;
+            });
+            call_args.push(quote!(#var));
+        } else {
+            required += 1;
+            bindings.push(quote! {
+                let #var = <#ty as ::jlisp::convert::FromExpr>::from_expr(
+                    args.next().unwrap(),
+                    name,
+                    span,
+                )?;
+            });
+            call_args.push(quote!(#var));
+        }
+    }
+
+    let arity = if rest {
+        quote!(::jlisp::builtin::Arity::AtLeast(#required))
+    } else {
+        quote!(::jlisp::builtin::Arity::Exact(#required))
+    };
+    let returns_result = match &func.sig.output {
+        ReturnType::Type(_, ty) => type_name(ty).as_deref() == Some("Result"),
+        ReturnType::Default => false,
+    };
+    let value = if returns_result {
+        quote!(#ident(#(#call_args),*)?)
+    } else {
+        quote!(#ident(#(#call_args),*))
+    };
+
+    Ok(quote! {
+        ::jlisp::inventory::submit! {
+            ::jlisp::builtin::BuiltinDef {
+                name: #name,
+                arity: #arity,
+                handler: {
+                    #[allow(unused_variables, unused_mut)]
+                    fn handler(
+                        env: &::jlisp::env::Env,
+                        name: &str,
+                        args: ::std::vec::Vec<::jlisp::ast::Expr>,
+                        span: ::jlisp::span::Span,
+                    ) -> ::std::result::Result<::jlisp::ast::Step, ::jlisp::ast::Error> {
+                        let mut args = args.into_iter();
+                        #(#bindings)*
+                        let value = #value;
+                        Ok(::jlisp::ast::Step::Done(::jlisp::convert::IntoExpr::into_expr(value)))
+                    }
+                    handler
+                },
+            }
+        }
+    })
+}
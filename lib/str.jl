@@ -0,0 +1,18 @@
+; string helpers that aren't builtins, `(import str)` makes them
+; str/capitalize, str/blank? and str/join-with
+
+(def [capitalize] (\ [s] [
+  if (== s "")
+    [s]
+    [str (upper (str-sub s 0 1)) (str-sub s 1 (len s))]
+]))
+
+(def [blank?] (\ [s] [== (trim s) ""]))
+
+(def [join-with] (\ [sep xs] [
+  if (== xs [])
+    [""]
+    [if (== (tail xs) [])
+      [str (first xs)]
+      [str (first xs) sep (join-with sep (tail xs))]]
+]))
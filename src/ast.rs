@@ -1,8 +1,24 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::mem;
+use std::sync::Arc;
 use std::vec::Vec;
 
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use regex::Regex;
+
+use crate::cells::Cells;
 use crate::env::Env;
+use crate::escape;
+use crate::number;
+use crate::span::Span;
+use crate::trace::{CallSite, Trace};
+use crate::vm::Evaluator;
 use thiserror::Error;
 
 #[derive(Debug)]
@@ -12,22 +28,44 @@ pub struct JLisp {
 
 #[derive(Debug, Clone)]
 pub enum Expr {
-    Number(i32),
-    Float(f32),
+    Nil,
+    Bool(bool),
+    Number(i64),
+    /// An integer too large for `Number`, see `crate::number`
+    BigInt(Arc<BigInt>),
+    /// An exact fraction that isn't a whole number
+    Rational(Arc<BigRational>),
+    Float(f64),
     Char(char),
-    Builtin(String),
+    Builtin(Arc<crate::builtin::Builtin>),
     Lambda {
         env: Env,
-        formals: Vec<Expr>,
-        body: Box<Expr>,
+        formals: Cells,
+        body: Arc<Expr>,
     },
+    /// A lambda that gets its args unevaluated and returns the code to
+    /// evaluate in their place
+    Macro(Arc<Expr>),
     Symbol(String),
     String(String),
     Comment(String),
-    Sexpr(Vec<Expr>),
-    List(Vec<Expr>),
+    Sexpr(Cells),
+    List(Cells),
+    /// `{k v ...}`. Like the items of a `[...]` list, the keys and values of
+    /// a literal are taken as written and not evaluated, so `{a 1}` is keyed
+    /// by the symbol `a`. Maps of computed values are built with `assoc`.
+    Map(Arc<Map>),
+    /// `#{...}`, whose items are taken as written like those of a map literal
+    Set(Arc<Set>),
+    /// A compiled regular expression, see `(regex pattern)`
+    Regex(Arc<Regex>),
 }
 
+/// Keys and values of a map, in insertion order
+pub type Map = IndexMap<Expr, Expr>;
+
+pub type Set = HashSet<Expr>;
+
 impl PartialEq for Expr {
     fn eq(&self, other: &Expr) -> bool {
         if mem::discriminant(self) != mem::discriminant(other) {
@@ -35,12 +73,31 @@ impl PartialEq for Expr {
         }
 
         match self {
+            Expr::Nil => true,
+            Expr::Bool(v) => {
+                let Expr::Bool(vother) = other else {
+                    panic!();
+                };
+                v == vother
+            }
             Expr::Number(v) => {
                 let Expr::Number(vother) = other else {
                     panic!();
                 };
                 v == vother
             }
+            Expr::BigInt(v) => {
+                let Expr::BigInt(vother) = other else {
+                    panic!();
+                };
+                v == vother
+            }
+            Expr::Rational(v) => {
+                let Expr::Rational(vother) = other else {
+                    panic!();
+                };
+                v == vother
+            }
             Expr::Float(v) => {
                 let Expr::Float(vother) = other else {
                     panic!();
@@ -70,7 +127,7 @@ impl PartialEq for Expr {
                 let Expr::Builtin(bother) = other else {
                     panic!();
                 };
-                b == bother
+                b.name() == bother.name()
             }
             Expr::Lambda {
                 env: _,
@@ -88,41 +145,102 @@ impl PartialEq for Expr {
 
                 (formals == other_formals) && (body == other_body)
             }
+            Expr::Macro(lambda) => {
+                let Expr::Macro(other_lambda) = other else {
+                    panic!();
+                };
+                lambda == other_lambda
+            }
             Expr::Sexpr(cells) => {
                 let Expr::Sexpr(cells_other) = other else {
                     panic!();
                 };
-                if cells.len() != cells_other.len() {
-                    return false;
-                }
-                for (c, o_c) in cells.iter().zip(cells_other.iter()) {
-                    if c != o_c {
-                        return false;
-                    }
-                }
-                true
+                cells == cells_other
             }
             Expr::List(cells) => {
                 let Expr::List(cells_other) = other else {
                     panic!();
                 };
-                if cells.len() != cells_other.len() {
-                    return false;
-                }
+                cells == cells_other
+            }
+            Expr::Map(map) => {
+                let Expr::Map(map_other) = other else {
+                    panic!();
+                };
+                map == map_other
+            }
+            Expr::Set(set) => {
+                let Expr::Set(set_other) = other else {
+                    panic!();
+                };
+                set == set_other
+            }
+            Expr::Regex(re) => {
+                let Expr::Regex(re_other) = other else {
+                    panic!();
+                };
+                re.as_str() == re_other.as_str()
+            }
+        }
+    }
+}
 
-                for (c, o_c) in cells.iter().zip(cells_other.iter()) {
-                    if c != o_c {
-                        return false;
-                    }
-                }
-                true
+impl Eq for Expr {}
+
+// agrees with `PartialEq`, so any value can be a map key
+impl Hash for Expr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Expr::Nil | Expr::Comment(_) => {}
+            Expr::Bool(b) => b.hash(state),
+            Expr::Number(n) => n.hash(state),
+            Expr::BigInt(n) => n.hash(state),
+            Expr::Rational(r) => r.hash(state),
+            // 0.0 and -0.0 are equal
+            Expr::Float(f) => (if *f == 0.0 { 0.0 } else { *f }).to_bits().hash(state),
+            Expr::Char(c) => c.hash(state),
+            Expr::Builtin(b) => b.name().hash(state),
+            Expr::Symbol(s) | Expr::String(s) => s.hash(state),
+            Expr::Lambda { formals, body, .. } => {
+                formals.hash(state);
+                body.hash(state);
             }
+            Expr::Macro(lambda) => lambda.hash(state),
+            Expr::Sexpr(cells) | Expr::List(cells) => cells.hash(state),
+            Expr::Map(map) => unordered_hash(map.iter(), state),
+            Expr::Set(set) => unordered_hash(set.iter(), state),
+            Expr::Regex(re) => re.as_str().hash(state),
         }
     }
 }
 
+// maps and sets are equal in any order, so combine the hashes of their
+// entries in a way that doesn't depend on it
+fn unordered_hash<T: Hash, H: Hasher>(entries: impl ExactSizeIterator<Item = T>, state: &mut H) {
+    entries.len().hash(state);
+    let mut sum: u64 = 0;
+    for entry in entries {
+        let mut h = DefaultHasher::new();
+        entry.hash(&mut h);
+        sum = sum.wrapping_add(h.finish());
+    }
+    sum.hash(state);
+}
+
+// the order sets are printed in: numbers by value, then everything else by
+// type and how it prints
+fn print_order(a: &Expr, b: &Expr) -> Ordering {
+    match (number::is_number(a), number::is_number(b)) {
+        (true, true) => number::compare(a, b).unwrap_or(Ordering::Equal),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => (a.as_str(), a.to_string()).cmp(&(b.as_str(), b.to_string())),
+    }
+}
+
 macro_rules! into_type {
-    ($self:expr, $variant:ident, $expected:expr, $op:expr, $line:expr) => {
+    ($self:expr, $variant:ident, $expected:expr, $op:expr, $span:expr) => {
         if let Expr::$variant(v) = $self {
             Ok(v)
         } else {
@@ -130,7 +248,7 @@ macro_rules! into_type {
                 op: $op.to_string(),
                 expected: $expected.to_string(),
                 received: $self.as_str(),
-                line: $line,
+                span: $span,
             })
         }
     };
@@ -140,47 +258,114 @@ macro_rules! into_type {
 impl Expr {
     pub fn as_str(&self) -> String {
         match self {
-            Expr::Number(_) => "Number".to_string(),
+            Expr::Nil => "Nil".to_string(),
+            Expr::Bool(_) => "Bool".to_string(),
+            Expr::Number(_) | Expr::BigInt(_) => "Number".to_string(),
+            Expr::Rational(_) => "Rational".to_string(),
             Expr::Float(_) => "Float".to_string(),
             Expr::Char(_) => "Char".to_string(),
             Expr::Builtin(_) => "Builtin".to_string(),
             Expr::Lambda { .. } => "Lambda".to_string(),
+            Expr::Macro(_) => "Macro".to_string(),
             Expr::Symbol(s) => format!("Symbol: {s}"),
             Expr::String(_) => "String".to_string(),
             Expr::Comment(_) => "Comment".to_string(),
             Expr::Sexpr(_) => "Sexpr".to_string(),
             Expr::List(_) => "List".to_string(),
+            Expr::Map(_) => "Map".to_string(),
+            Expr::Set(_) => "Set".to_string(),
+            Expr::Regex(_) => "Regex".to_string(),
+        }
+    }
+
+    /// Whether a condition holds: `false`, `nil`, `0`, `0.0`, `""` and empty
+    /// lists, forms, maps and sets are false, everything else is true. `if`, `and`, `or`
+    /// and `not` all go by this.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Expr::Nil => false,
+            Expr::Bool(b) => *b,
+            Expr::Number(n) => *n != 0,
+            // never zero, that would be a `Number`
+            Expr::BigInt(_) | Expr::Rational(_) => true,
+            Expr::Float(f) => *f != 0.0,
+            Expr::String(s) => !s.is_empty(),
+            Expr::List(cells) | Expr::Sexpr(cells) => !cells.is_empty(),
+            Expr::Map(map) => !map.is_empty(),
+            Expr::Set(set) => !set.is_empty(),
+            _ => true,
         }
     }
 
     #[inline(always)]
-    pub fn into_sexpr(self, op: &str, line: usize) -> Result<Vec<Expr>, Error> {
-        into_type!(self, Sexpr, "Sexpr", op, line)
+    pub fn into_sexpr(self, op: &str, span: Span) -> Result<Cells, Error> {
+        into_type!(self, Sexpr, "Sexpr", op, span)
+    }
+
+    #[inline(always)]
+    pub fn into_number(self, op: &str, span: Span) -> Result<i64, Error> {
+        match self {
+            Expr::Number(n) => Ok(n),
+            Expr::BigInt(_) => Err(Error::IncompatibleType {
+                op: op.to_string(),
+                expected: "Number that fits in 64 bits".to_string(),
+                received: "big Number".to_string(),
+                span,
+            }),
+            _ => into_type!(self, Number, "Number", op, span),
+        }
+    }
+
+    #[inline(always)]
+    pub fn into_list(self, op: &str, span: Span) -> Result<Cells, Error> {
+        into_type!(self, List, "List", op, span)
+    }
+
+    /// The elements of a list, or of a form a macro was handed
+    #[inline(always)]
+    pub fn into_cells(self, op: &str, span: Span) -> Result<Cells, Error> {
+        match self {
+            Expr::List(cells) | Expr::Sexpr(cells) => Ok(cells),
+            _ => Err(Error::IncompatibleType {
+                op: op.to_string(),
+                expected: "List".to_string(),
+                received: self.as_str(),
+                span,
+            }),
+        }
+    }
+
+    /// The form a quoted list stands for, anything else is already one
+    pub fn into_form(self) -> Expr {
+        match self {
+            Expr::List(cells) => Expr::Sexpr(cells),
+            expr => expr,
+        }
     }
 
     #[inline(always)]
-    pub fn into_number(self, op: &str, line: usize) -> Result<i32, Error> {
-        into_type!(self, Number, "Number", op, line)
+    pub fn into_map(self, op: &str, span: Span) -> Result<Arc<Map>, Error> {
+        into_type!(self, Map, "Map", op, span)
     }
 
     #[inline(always)]
-    pub fn into_list(self, op: &str, line: usize) -> Result<Vec<Expr>, Error> {
-        into_type!(self, List, "List", op, line)
+    pub fn into_set(self, op: &str, span: Span) -> Result<Arc<Set>, Error> {
+        into_type!(self, Set, "Set", op, span)
     }
 
     #[inline(always)]
-    pub fn into_symbol(self, op: &str, line: usize) -> Result<String, Error> {
-        into_type!(self, Symbol, "Symbol", op, line)
+    pub fn into_symbol(self, op: &str, span: Span) -> Result<String, Error> {
+        into_type!(self, Symbol, "Symbol", op, span)
     }
 
     #[inline(always)]
-    pub fn into_string(self, op: &str, line: usize) -> Result<String, Error> {
-        into_type!(self, String, "String", op, line)
+    pub fn into_string(self, op: &str, span: Span) -> Result<String, Error> {
+        into_type!(self, String, "String", op, span)
     }
 }
 // arity helper
 #[inline(always)]
-pub fn expect_arity(func: &str, args: &[Expr], n: usize, line: usize) -> Result<(), Error> {
+pub fn expect_arity(func: &str, args: &[Expr], n: usize, span: Span) -> Result<(), Error> {
     if args.len() == n {
         Ok(())
     } else {
@@ -188,18 +373,18 @@ pub fn expect_arity(func: &str, args: &[Expr], n: usize, line: usize) -> Result<
             func: func.to_string(),
             expected: n,
             received: args.len(),
-            line,
+            span,
         })
     }
 }
 #[inline(always)]
-pub fn expect_nonempty(func: &str, args: &[Expr], line: usize) -> Result<(), Error> {
+pub fn expect_nonempty(func: &str, args: &[Expr], span: Span) -> Result<(), Error> {
     if args.is_empty() {
         return Err(Error::WrongAmountOfArgs {
             func: func.to_string(),
             expected: 1,
             received: args.len(),
-            line,
+            span,
         });
     }
     Ok(())
@@ -208,7 +393,11 @@ pub fn expect_nonempty(func: &str, args: &[Expr], line: usize) -> Result<(), Err
 impl fmt::Display for Expr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Expr::Nil => write!(f, "nil"),
+            Expr::Bool(v) => write!(f, "{}", v),
             Expr::Number(v) => write!(f, "{}", v),
+            Expr::BigInt(v) => write!(f, "{}", v),
+            Expr::Rational(v) => write!(f, "{}", v),
             Expr::Float(v) => {
                 if v.fract() == 0.0 {
                     write!(f, "{:.1}", v)
@@ -218,7 +407,11 @@ impl fmt::Display for Expr {
             }
             Expr::Symbol(v) => write!(f, "{}", v),
             Expr::Char(v) => write!(f, "'{}'", v.escape_default()),
-            Expr::String(v) => write!(f, "\"{}\"", v),
+            Expr::String(v) => {
+                write!(f, "\"")?;
+                escape::escape(v, f)?;
+                write!(f, "\"")
+            }
             Expr::Lambda {
                 env: _,
                 formals,
@@ -233,6 +426,16 @@ impl fmt::Display for Expr {
                 }
                 write!(f, "{})", body)
             }
+            Expr::Macro(lambda) => match lambda.as_ref() {
+                Expr::Lambda { formals, body, .. } => {
+                    write!(f, "(macro")?;
+                    for form in formals.iter() {
+                        write!(f, " {}", form)?;
+                    }
+                    write!(f, " {})", body)
+                }
+                _ => write!(f, "<macro>"),
+            },
             Expr::Builtin(_) => write!(f, "<builtin>"),
             Expr::Sexpr(vals) => {
                 write!(f, "(")?;
@@ -254,6 +457,34 @@ impl fmt::Display for Expr {
                 }
                 write!(f, "]")
             }
+            Expr::Map(map) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in map.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{} {}", k, v)?;
+                }
+                write!(f, "}}")
+            }
+            Expr::Set(set) => {
+                let mut items: Vec<&Expr> = set.iter().collect();
+                items.sort_by(|a, b| print_order(a, b));
+                write!(f, "#{{")?;
+                for (i, v) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", v)?;
+                }
+                write!(f, "}}")
+            }
+            // the form that compiles it again
+            Expr::Regex(re) => {
+                write!(f, "(regex \"")?;
+                escape::escape(re.as_str(), f)?;
+                write!(f, "\")")
+            }
             Expr::Comment(_) => write!(f, "()"),
         }
     }
@@ -261,38 +492,139 @@ impl fmt::Display for Expr {
 
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("undefined symbol '{sym}' at line {line}")]
-    UndefinedSymbol { sym: String, line: usize },
-    #[error("Tried to divide by zero at line {line}")]
-    DivisionByZero { line: usize },
-    #[error("type error in '{op}' expected {expected}, got {received} at line {line}")]
+    #[error("undefined symbol '{sym}' at {span}")]
+    UndefinedSymbol { sym: String, span: Span },
+    #[error("Tried to divide by zero at {span}")]
+    DivisionByZero { span: Span },
+    #[error("type error in '{op}' expected {expected}, got {received} at {span}")]
     IncompatibleType {
         op: String,
         expected: String,
         received: String,
-        line: usize,
+        span: Span,
     },
-    #[error("mixed types in '{op}' at line {line}")]
-    InconsistentTypes { op: String, line: usize },
-    #[error("missing operator at line {line}")]
-    MissingOperator { line: usize },
+    #[error("mixed types in '{op}' at {span}")]
+    InconsistentTypes { op: String, span: Span },
+    #[error("missing operator at {span}")]
+    MissingOperator { span: Span },
     #[error(
-        "wrong amount of args to func '{func}', expected {expected} but got {received} at line {line}"
+        "wrong amount of args to func '{func}', expected {expected} but got {received} at {span}"
     )]
     WrongAmountOfArgs {
         func: String,
         expected: usize,
         received: usize,
-        line: usize,
+        span: Span,
     },
-    #[error("IO error: {msg} at line {line}")]
-    IoError { msg: String, line: usize },
-    #[error("Parse error: {msg} at line {line}")]
-    ParseError { msg: String, line: usize },
+    #[error("IO error: {msg} at {span}")]
+    IoError { msg: String, span: Span },
+    #[error("Parse error: {msg} at {span}")]
+    ParseError { msg: String, span: Span },
+    /// An argument of the right type that is outside what a builtin can
+    /// work with, like a modulus with no inverse or a huge power
+    #[error("domain error: {msg} at {span}")]
+    Domain { msg: String, span: Span },
+    /// A module that can't be imported, like one that imports itself
+    #[error("import error: {msg} at {span}")]
+    Import { msg: String, span: Span },
+    /// Raised by `(error msg)` or `(error msg payload)`
+    #[error("error: {msg} at {span}")]
+    User {
+        msg: String,
+        payload: Option<Box<Expr>>,
+        span: Span,
+    },
+    #[error("{error}\n{trace}")]
+    Traced { error: Box<Error>, trace: Trace },
+}
+
+impl Error {
+    /// Where the error was raised
+    pub fn span(&self) -> Span {
+        match self {
+            Error::UndefinedSymbol { span, .. }
+            | Error::DivisionByZero { span }
+            | Error::IncompatibleType { span, .. }
+            | Error::InconsistentTypes { span, .. }
+            | Error::MissingOperator { span }
+            | Error::WrongAmountOfArgs { span, .. }
+            | Error::IoError { span, .. }
+            | Error::ParseError { span, .. }
+            | Error::Domain { span, .. }
+            | Error::Import { span, .. }
+            | Error::User { span, .. } => *span,
+            Error::Traced { error, .. } => error.span(),
+        }
+    }
+
+    /// What went wrong, as `try` hands it to a catch clause
+    pub fn kind(&self) -> &'static str {
+        match self.root() {
+            Error::UndefinedSymbol { .. } => "undefined-symbol",
+            Error::DivisionByZero { .. } => "division-by-zero",
+            Error::IncompatibleType { .. }
+            | Error::InconsistentTypes { .. }
+            | Error::MissingOperator { .. } => "type",
+            Error::WrongAmountOfArgs { .. } => "arity",
+            Error::IoError { .. } => "io",
+            Error::ParseError { .. } => "parse",
+            Error::Domain { .. } => "domain",
+            Error::Import { .. } => "import",
+            Error::User { .. } | Error::Traced { .. } => "user",
+        }
+    }
+
+    /// The message of the error, without where it was raised or the calls
+    /// it was raised in
+    pub fn message(&self) -> String {
+        let root = self.root();
+        if let Error::User { msg, .. } = root {
+            return msg.clone();
+        }
+        let full = root.to_string();
+        let at = format!(" at {}", root.span());
+        full.strip_suffix(&at).unwrap_or(&full).to_string()
+    }
+
+    /// The error without the calls it was raised in
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Traced { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// The calls the error was raised in, outermost first
+    pub fn trace(&self) -> Option<&Trace> {
+        match self {
+            Error::Traced { trace, .. } => Some(trace),
+            _ => None,
+        }
+    }
+
+    /// Record that the error unwound out of `call`
+    pub(crate) fn called_from(self, call: &CallSite) -> Error {
+        let (error, mut trace) = match self {
+            Error::Traced { error, trace } => (error, trace),
+            error => (Box::new(error), Trace::default()),
+        };
+        trace.push_outer(call.into());
+        Error::Traced { error, trace }
+    }
+}
+
+/// What a builtin or lambda application hands back to the evaluator.
+///
+/// `Eval` is a form left in tail position (an `if` branch, the last form of a
+/// body), which the evaluator runs in place of the current call instead of
+/// recursing, so tail-recursive code runs in constant stack.
+pub enum Step {
+    Done(Expr),
+    Eval(Expr, Env),
 }
 
 #[inline(always)]
-fn _eval_lambda(_env: Env, op: Expr, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
+pub(crate) fn apply_lambda(op: Expr, args: Vec<Expr>, span: Span) -> Result<Step, Error> {
     let func = "\\";
     let Expr::Lambda {
         env: e,
@@ -304,114 +636,225 @@ fn _eval_lambda(_env: Env, op: Expr, args: Vec<Expr>, line: usize) -> Result<Exp
     };
 
     // Create a child environment for the lambda execution
-    let lambda_env = Env::child(e);
-    
-    // Bind arguments to the lambda's environment
-    let mut i = 0;
-    while i < args.len() {
-        let val = args.get(i).unwrap();
-        expect_nonempty(func, &formals, line)?;
-        let sym = formals.remove(0).into_symbol(func, line)?;
+    let lambda_env = Env::child(&e);
+
+    // Bind arguments to the lambda's environment, all at once below
+    let mut bound = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(val) = args.next() {
+        expect_nonempty(func, &formals, span)?;
+        let sym = formals[0].clone().into_symbol(func, span)?;
+        formals = formals.tail();
 
         if sym == "&" {
             // require one more symbol
-            expect_nonempty(func, &formals, line)?;
-            let rest_sym = formals.remove(0).into_symbol(func, line)?;
+            expect_nonempty(func, &formals, span)?;
+            let rest_sym = formals[0].clone().into_symbol(func, span)?;
+            formals = formals.tail();
 
             // take rest of args and quit loop
-            let rest_args: Vec<_> = args.iter().skip(i + 1).cloned().collect();
-            lambda_env.insert(rest_sym.clone(), Expr::List(rest_args));
+            let rest_args: Cells = std::iter::once(val).chain(args.by_ref()).collect();
+            bound.push((rest_sym, Expr::List(rest_args)));
             break;
         }
 
-        lambda_env.insert(sym.clone(), val.clone());
-        i += 1;
+        bound.push((sym, val));
     }
 
     // handle case where the & arg needs to bind to an empty list
     if !formals.is_empty() {
-        let list_sym = formals.first().unwrap().clone().into_symbol(func, line)?;
+        let list_sym = formals[0].clone().into_symbol(func, span)?;
         if list_sym == "&" {
-            expect_arity(func, &formals, 2, line)?;
-            let bind_sym = formals.get(1).unwrap().clone().into_symbol(func, line)?;
-            lambda_env.insert(bind_sym.clone(), Expr::List(Vec::new()));
-            formals.clear();
+            expect_arity(func, &formals, 2, span)?;
+            let bind_sym = formals[1].clone().into_symbol(func, span)?;
+            bound.push((bind_sym, Expr::List(Cells::new())));
+            formals = Cells::new();
         }
     }
 
+    lambda_env.extend(bound);
+
     if formals.is_empty() {
-        // All args bound, evaluate the body
-        // TODO: I think we can do some garbage collection of the Envs right here
-        // by using the env.remove() method afer the eval since we know the env is no longer needed
+        // All args bound, hand the body back to the evaluator
         match body.as_ref() {
             Expr::List(inner) => {
                 if inner.is_empty() {
-                    Ok(Expr::Sexpr(Vec::new()))
-                } else if !inner.is_empty() && matches!(inner[0], Expr::Symbol(_)) {
+                    Ok(Step::Done(Expr::Sexpr(Cells::new())))
+                } else if matches!(inner[0], Expr::Symbol(_)) {
                     // Starts with symbol - treat as S-expression
-                    Expr::Sexpr(inner.clone()).eval(lambda_env, line)
+                    Ok(Step::Eval(Expr::Sexpr(inner.clone()), lambda_env))
                 } else {
                     // Multiple expressions not starting with symbol - use sequential evaluation
-                    crate::builtin::builtin_eval(func, lambda_env, vec![*body.clone()], line)
+                    crate::builtin::builtin_eval(
+                        func,
+                        &lambda_env,
+                        vec![Expr::List(inner.clone())],
+                        span,
+                    )
                 }
             }
-            _ => body.eval(lambda_env, line),
+            body => Ok(Step::Eval(body.clone(), lambda_env)),
         }
     } else {
-        // Partial application - return partial lambda
-        Ok(Expr::Lambda {
-            env: e,
+        // Partial application - return partial lambda, keeping what was bound so far
+        Ok(Step::Done(Expr::Lambda {
+            env: lambda_env,
             formals,
             body,
-        })
+        }))
+    }
+}
+
+/// Call a builtin or lambda from inside a builtin, running it to completion
+pub(crate) fn call(env: &Env, f: Expr, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let step = match f {
+        Expr::Builtin(b) => b.call(env, args, span)?,
+        Expr::Lambda { .. } => apply_lambda(f, args, span)?,
+        _ => return Err(Error::MissingOperator { span }),
+    };
+    match step {
+        Step::Done(v) => Ok(v),
+        Step::Eval(expr, env) => expr.eval(&env, span),
     }
 }
 
+/// Run a macro on the unevaluated args of `site`, returns the code it
+/// expanded to
+pub(crate) fn expand_macro(op: Expr, args: Vec<Expr>, site: &CallSite) -> Result<Expr, Error> {
+    let Expr::Macro(lambda) = op else {
+        panic!();
+    };
+    let expansion = match apply_lambda(lambda.as_ref().clone(), args, site.span) {
+        Ok(Step::Done(v)) => Ok(v),
+        Ok(Step::Eval(body, env)) => body.eval(&env, site.span),
+        Err(err) => Err(err),
+    };
+    expansion.map_err(|err| err.called_from(site))
+}
+
 impl Expr {
+    /// Evaluate on the evaluator of the interpreter `env` belongs to
+    pub fn eval(self, env: &Env, span: Span) -> Result<Expr, Error> {
+        match env.state().evaluator() {
+            Evaluator::Vm => crate::vm::eval(self, env, span),
+            Evaluator::TreeWalk => self.walk(env, span),
+        }
+    }
+
     #[inline(always)] // a load bearing inline
-    pub fn eval(self, env: Env, line: usize) -> Result<Expr, Error> {
-        match self {
-            Expr::Number(_)
-            | Expr::Float(_)
-            | Expr::Char(_)
-            | Expr::String(_)
-            | Expr::List(_)
-            | Expr::Builtin(_)
-            | Expr::Lambda { .. } => Ok(self),
-            Expr::Comment(_) => Ok(Expr::Sexpr(Vec::new())),
-            Expr::Symbol(sym) => match env.get(&sym) {
-                Some(v) => Ok(v),
-                None => Err(Error::UndefinedSymbol { sym, line }),
-            },
-            Expr::Sexpr(sexpr) => {
-                if sexpr.is_empty() {
-                    return Ok(Expr::Sexpr(Vec::new()));
-                } else if sexpr.len() == 1 {
-                    return sexpr[0].clone().eval(env, line);
-                }
+    fn walk(self, env: &Env, span: Span) -> Result<Expr, Error> {
+        let mut call = None;
+        self.walk_calls(env, span, &mut call)
+            .map_err(|err| match &call {
+                Some(call) => err.called_from(call),
+                None => err,
+            })
+    }
 
-                let op = sexpr[0].clone().eval(env, line)?;
-                let args = sexpr[1..].to_vec();
+    /// Walk `self`, `call` is the lambda call currently being evaluated
+    #[inline(always)]
+    fn walk_calls(self, env: &Env, span: Span, call: &mut Option<CallSite>) -> Result<Expr, Error> {
+        let mut expr = self;
+        let mut env = env.clone();
+        let mut span = span;
 
-                // Special handling for lambda and fun builtin - don't evaluate arguments
-                if let Expr::Builtin(sym) = &op {
-                    if sym == "\\" || sym == "fun" {
-                        return crate::builtin::eval_builtin(env, sym.as_str(), args, line);
-                    }
+        // forms in tail position come back as `Step::Eval` and loop here
+        loop {
+            let step = match expr {
+                Expr::Nil
+                | Expr::Bool(_)
+                | Expr::Number(_)
+                | Expr::BigInt(_)
+                | Expr::Rational(_)
+                | Expr::Float(_)
+                | Expr::Char(_)
+                | Expr::String(_)
+                | Expr::List(_)
+                | Expr::Map(_)
+                | Expr::Set(_)
+                | Expr::Regex(_)
+                | Expr::Builtin(_)
+                | Expr::Lambda { .. }
+                | Expr::Macro(_) => return Ok(expr),
+                Expr::Comment(_) => return Ok(Expr::Sexpr(Cells::new())),
+                Expr::Symbol(sym) => {
+                    return match env.get(&sym) {
+                        Some(v) => Ok(v),
+                        None => Err(Error::UndefinedSymbol { sym, span }),
+                    };
                 }
+                Expr::Sexpr(sexpr) => {
+                    // forms built at runtime report the span of the form that built them
+                    span = sexpr.span().unwrap_or(span);
+                    if sexpr.is_empty() {
+                        return Ok(Expr::Sexpr(Cells::new()));
+                    } else if sexpr.len() == 1 {
+                        let Expr::Symbol(sym) = &sexpr[0] else {
+                            expr = sexpr[0].clone();
+                            continue;
+                        };
+                        // a lone builtin that takes no arguments is called,
+                        // anything else evaluates to itself
+                        match env.get(sym) {
+                            Some(Expr::Builtin(b)) if b.is_nullary() => {
+                                b.call(&env, Vec::new(), span)?
+                            }
+                            Some(v) => return Ok(v),
+                            None => {
+                                return Err(Error::UndefinedSymbol {
+                                    sym: sym.clone(),
+                                    span,
+                                });
+                            }
+                        }
+                    } else {
+                        let op = sexpr[0].clone().walk(&env, span)?;
+                        let args = sexpr.tail();
 
-                // Evaluate all arguments for other operators
-                let evaluated_args = args
-                    .into_iter()
-                    .map(|e| e.eval(env, line))
-                    .collect::<Result<Vec<_>, _>>()?;
+                        // Special forms get their arguments unevaluated
+                        if let Expr::Builtin(b) = &op
+                            && b.is_special_form()
+                        {
+                            b.call(&env, args.into_vec(), span)?
+                        } else if let Expr::Macro(_) = op {
+                            // so do macros, their expansion runs in place of the call
+                            let site = CallSite {
+                                form: sexpr.clone(),
+                                span,
+                            };
+                            let expansion = expand_macro(op, args.into_vec(), &site)?;
+                            Step::Eval(expansion.into_form(), env.clone())
+                        } else {
+                            // Evaluate all arguments for other operators
+                            let evaluated_args = args
+                                .iter()
+                                .map(|e| e.clone().walk(&env, span))
+                                .collect::<Result<Vec<_>, _>>()?;
 
-                match op {
-                    Expr::Builtin(sym) => {
-                        crate::builtin::eval_builtin(env, sym.as_str(), evaluated_args, line)
+                            match op {
+                                Expr::Builtin(b) => b.call(&env, evaluated_args, span)?,
+                                Expr::Lambda { .. } => {
+                                    let site = CallSite {
+                                        form: sexpr.clone(),
+                                        span,
+                                    };
+                                    let step = apply_lambda(op, evaluated_args, span)
+                                        .map_err(|err| err.called_from(&site))?;
+                                    *call = Some(site);
+                                    step
+                                }
+                                _ => return Err(Error::MissingOperator { span }),
+                            }
+                        }
                     }
-                    Expr::Lambda { .. } => _eval_lambda(env, op, evaluated_args, line),
-                    _ => Err(Error::MissingOperator { line }),
+                }
+            };
+
+            match step {
+                Step::Done(v) => return Ok(v),
+                Step::Eval(next, next_env) => {
+                    expr = next;
+                    env = next_env;
                 }
             }
         }
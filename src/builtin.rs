@@ -2,131 +2,459 @@ mod arithmetic;
 mod collections;
 mod comparison;
 mod core;
+mod errors;
+mod expand;
+mod format;
 mod helpers;
 mod io;
 mod macros;
+mod maps;
 mod math;
+mod memory;
+mod modules;
+mod regexes;
+mod sets;
 mod strings;
 
-use crate::ast::{Error, Expr};
+use crate::ast::{Error, Expr, Step};
 use crate::env::Env;
-
-/// Macro to generate builtin function cases
-macro_rules! builtin_cases {
-    ($sym:expr, $args:expr, $line:expr, $($name:expr => $handler:expr),*) => {
-        match $sym {
-            $($name => $handler($sym, $args, $line),)*
-            _ => panic!(),
-        }
-    };
-}
-
-/// Macro to generate builtin list
-macro_rules! builtin_list {
-    ($($name:expr),*) => {
-        [$($name),*]
-    };
-}
+use crate::number;
+use crate::span::Span;
+use std::fmt;
+use std::sync::Arc;
 
 // Re-export all the builtin functions
-use arithmetic::builtin_op;
+use arithmetic::{builtin_number_theory, builtin_op};
 use collections::{
     builtin_head, builtin_join, builtin_last, builtin_len, builtin_list, builtin_split,
     builtin_tail,
 };
 use comparison::{builtin_comp, builtin_logic, builtin_ord};
-pub use core::{builtin_eval, builtin_fun, builtin_if, builtin_lambda, builtin_var};
+pub use core::{builtin_do, builtin_eval, builtin_fun, builtin_if, builtin_lambda, builtin_var};
+use errors::{builtin_error, builtin_try};
+use expand::{builtin_macroexpand, builtin_quasiquote};
+use format::{builtin_format, builtin_repr, builtin_str};
 use helpers::{builtin_print, builtin_range, builtin_sort};
-use io::{builtin_load, builtin_read, builtin_error};
+use io::{builtin_load, builtin_read};
+pub(crate) use io::{eval_file, eval_source};
+use maps::{
+    builtin_assoc, builtin_dissoc, builtin_get, builtin_has, builtin_keys, builtin_merge,
+    builtin_update, builtin_vals,
+};
 use math::{
-    builtin_abs, builtin_ceil, builtin_cos, builtin_exp, builtin_floor, builtin_log, builtin_max,
-    builtin_min, builtin_round, builtin_sin, builtin_sqrt, builtin_tan, builtin_truncate,
+    builtin_abs, builtin_ceil, builtin_cos, builtin_exact_to_inexact, builtin_exp, builtin_floor,
+    builtin_format_float, builtin_fraction_part, builtin_log, builtin_max, builtin_min,
+    builtin_round, builtin_sin, builtin_sqrt, builtin_tan, builtin_truncate,
 };
-use strings::{builtin_chars, builtin_int, builtin_str_sub};
-
-#[inline(always)]
-pub fn eval_builtin(env: Env, sym: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    // Handle arithmetic operators
-    if matches!(
-        sym,
-        "+" | "-" | "*" | "/" | "%" | "**" | "&" | "|" | "^" | "<<" | ">>"
-    ) {
-        return builtin_op(sym, args, line);
+use memory::{builtin_gc, builtin_gc_stats};
+use modules::builtin_import;
+use regexes::{
+    builtin_re_captures, builtin_re_find_all, builtin_re_match, builtin_re_replace,
+    builtin_re_split, builtin_regex,
+};
+use sets::{
+    builtin_member, builtin_set, builtin_set_add, builtin_set_op, builtin_set_remove,
+    builtin_subset,
+};
+use strings::{
+    builtin_affix, builtin_case, builtin_char_class, builtin_chars, builtin_index_of, builtin_int,
+    builtin_lines_words, builtin_list_to_string, builtin_pad, builtin_repeat, builtin_replace,
+    builtin_reverse, builtin_trim,
+};
+
+/// How many arguments a builtin takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+    /// The builtin checks its arguments itself
+    Any,
+}
+
+/// What a builtin registered with `register` takes for an argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Any,
+    Nil,
+    Bool,
+    /// An integer that fits in 64 bits, what `Expr::into_number` accepts
+    Number,
+    /// An integer of any size
+    Integer,
+    Rational,
+    Float,
+    /// A number of any kind, integer, rational or float
+    Numeric,
+    Char,
+    String,
+    Symbol,
+    List,
+    Sexpr,
+    Map,
+    Set,
+    Regex,
+    /// A builtin or a lambda
+    Function,
+}
+
+impl Type {
+    pub fn accepts(self, arg: &Expr) -> bool {
+        match self {
+            Type::Any => true,
+            Type::Nil => matches!(arg, Expr::Nil),
+            Type::Bool => matches!(arg, Expr::Bool(_)),
+            Type::Number => matches!(arg, Expr::Number(_)),
+            Type::Integer => matches!(arg, Expr::Number(_) | Expr::BigInt(_)),
+            Type::Rational => matches!(arg, Expr::Rational(_)),
+            Type::Float => matches!(arg, Expr::Float(_)),
+            Type::Numeric => number::is_number(arg),
+            Type::Char => matches!(arg, Expr::Char(_)),
+            Type::String => matches!(arg, Expr::String(_)),
+            Type::Symbol => matches!(arg, Expr::Symbol(_)),
+            Type::List => matches!(arg, Expr::List(_)),
+            Type::Sexpr => matches!(arg, Expr::Sexpr(_)),
+            Type::Map => matches!(arg, Expr::Map(_)),
+            Type::Set => matches!(arg, Expr::Set(_)),
+            Type::Regex => matches!(arg, Expr::Regex(_)),
+            Type::Function => matches!(arg, Expr::Builtin(_) | Expr::Lambda { .. }),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Type::Any => "Any",
+            Type::Nil => "Nil",
+            Type::Bool => "Bool",
+            Type::Number | Type::Integer => "Number",
+            Type::Rational => "Rational",
+            Type::Float => "Float",
+            Type::Numeric => "Number, Rational or Float",
+            Type::Char => "Char",
+            Type::String => "String",
+            Type::Symbol => "Symbol",
+            Type::List => "List",
+            Type::Sexpr => "Sexpr",
+            Type::Map => "Map",
+            Type::Set => "Set",
+            Type::Regex => "Regex",
+            Type::Function => "Function",
+        }
+    }
+}
+
+type Handler = dyn Fn(&Env, &str, Vec<Expr>, Span) -> Result<Step, Error> + Send + Sync;
+
+/// A function implemented in Rust, what `Expr::Builtin` calls
+pub struct Builtin {
+    name: String,
+    arity: Arity,
+    types: Vec<Type>,
+    special: bool,
+    handler: Box<Handler>,
+}
+
+impl Builtin {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    /// The types of its arguments, the last one also covers the rest
+    pub fn types(&self) -> &[Type] {
+        &self.types
     }
 
-    // Handle comparison operators
-    if matches!(sym, "==" | "!=") {
-        return builtin_comp(sym, args, line);
+    /// Whether it receives its arguments unevaluated
+    pub fn is_special_form(&self) -> bool {
+        self.special
     }
 
-    // Handle ordering operators
-    if matches!(sym, ">" | "<" | ">=" | "<=") {
-        return builtin_ord(sym, args, line);
+    /// Whether a lone `(name)` calls it
+    pub fn is_nullary(&self) -> bool {
+        self.arity == Arity::Exact(0)
     }
 
-    // Handle logic operators
-    if matches!(sym, "and" | "or" | "not") {
-        return builtin_logic(sym, args, line);
+    // the arguments match the arity and types it was registered with
+    fn check(&self, args: &[Expr], span: Span) -> Result<(), Error> {
+        let expected = match self.arity {
+            Arity::Exact(n) if args.len() != n => Some(n),
+            Arity::AtLeast(n) if args.len() < n => Some(n),
+            _ => None,
+        };
+        if let Some(expected) = expected {
+            return Err(Error::WrongAmountOfArgs {
+                func: self.name.clone(),
+                expected,
+                received: args.len(),
+                span,
+            });
+        }
+        // the last type also covers the rest of a variadic call
+        let types = self
+            .types
+            .iter()
+            .chain(self.types.last().into_iter().cycle());
+        for (arg, &ty) in args.iter().zip(types) {
+            if !ty.accepts(arg) {
+                let received = match arg {
+                    // the way `into_number` reports it
+                    Expr::BigInt(_) => "big Number".to_string(),
+                    _ => arg.as_str(),
+                };
+                return Err(Error::IncompatibleType {
+                    op: self.name.clone(),
+                    expected: ty.name().to_string(),
+                    received,
+                    span,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Call it with `args`, evaluated unless it is a special form
+    #[inline(always)]
+    pub fn call(&self, env: &Env, args: Vec<Expr>, span: Span) -> Result<Step, Error> {
+        self.check(&args, span)?;
+        (self.handler)(env, &self.name, args, span)
     }
+}
 
-    // Handle builtin functions
-    builtin_cases!(sym, args, line,
-        "head" => builtin_head,
-        "last" => builtin_last,
-        "tail" => builtin_tail,
-        "list" => builtin_list,
-        "join" => builtin_join,
-        "eval" => |s, a, l| builtin_eval(s, env, a, l),
-        "def" => |_s, a, l| builtin_var(env, "def", a, l),
-        "=" => |_s, a, l| builtin_var(env, "=", a, l),
-        "\\" => |s, a, l| builtin_lambda(s, env, a, l),
-        "print" => builtin_print,
-        "range" => builtin_range,
-        "if" => |s, a, l| builtin_if(s, env, a, l),
-        "load" => |s, a, l| builtin_load(s, env, a, l),
-        "read" => builtin_read,
-        "error" => builtin_error,
-        "fun" => |s, a, l| builtin_fun(s, env, a, l),
-        "chars" => builtin_chars,
-        "int" => builtin_int,
-        "sort" => builtin_sort,
-        "len" => builtin_len,
-        "str-sub" => builtin_str_sub,
-        "split" => builtin_split,
-        "sqrt" => builtin_sqrt,
-        "abs" => builtin_abs,
-        "min" => builtin_min,
-        "max" => builtin_max,
-        "floor" => builtin_floor,
-        "ceil" => builtin_ceil,
-        "round" => builtin_round,
-        "sin" => builtin_sin,
-        "cos" => builtin_cos,
-        "tan" => builtin_tan,
-        "log" => builtin_log,
-        "exp" => builtin_exp,
-        "truncate" => builtin_truncate
-    )
+impl fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Builtin({})", self.name)
+    }
 }
 
+/// Bind `name` in `env` to the Rust function `f`, which is called with the
+/// evaluated arguments. A call whose arguments don't match `arity`, or the
+/// `types` given for them, is an error before `f` runs.
+pub fn register<F>(env: &Env, name: &str, arity: Arity, types: &[Type], f: F)
+where
+    F: Fn(Vec<Expr>, Span) -> Result<Expr, Error> + Send + Sync + 'static,
+{
+    let builtin = Builtin {
+        name: name.to_string(),
+        arity,
+        types: types.to_vec(),
+        special: false,
+        handler: Box::new(move |_, _, args, span| f(args, span).map(Step::Done)),
+    };
+    env.insert(name.to_string(), Expr::Builtin(Arc::new(builtin)));
+}
+
+/// A builtin declared with `#[jlisp::builtin]`, every env `setup_builtins`
+/// makes gets the ones linked into the program
+pub struct BuiltinDef {
+    pub name: &'static str,
+    pub arity: Arity,
+    pub handler: fn(&Env, &str, Vec<Expr>, Span) -> Result<Step, Error>,
+}
+
+inventory::collect!(BuiltinDef);
+
+// how a builtin in `BUILTINS` wants to be called
+#[derive(Clone, Copy)]
+enum Kind {
+    Plain(fn(&str, Vec<Expr>, Span) -> Result<Expr, Error>),
+    Env(fn(&str, &Env, Vec<Expr>, Span) -> Result<Expr, Error>),
+    /// Leaves its last step in tail position
+    Step(fn(&str, &Env, Vec<Expr>, Span) -> Result<Step, Error>),
+}
+
+use Arity::{AtLeast, Exact};
+use Kind::{Env as WithEnv, Plain, Step as Stepping};
+use Type as T;
+
+// Every builtin with the arguments it takes, the last type covering the
+// rest of them, and whether it receives them unevaluated. `Exact(0)` ones
+// are called by a lone `(name)`.
+#[rustfmt::skip]
+static BUILTINS: &[(&str, Kind, Arity, &[Type], bool)] = &[
+    ("+", Plain(builtin_op), AtLeast(1), &[], false),
+    ("-", Plain(builtin_op), AtLeast(1), &[], false),
+    ("*", Plain(builtin_op), AtLeast(1), &[], false),
+    ("/", Plain(builtin_op), AtLeast(1), &[], false),
+    ("%", Plain(builtin_op), Exact(2), &[T::Numeric], false),
+    ("**", Plain(builtin_op), Exact(2), &[T::Numeric], false),
+    ("&", Plain(builtin_op), AtLeast(1), &[T::Integer], false),
+    ("|", Plain(builtin_op), AtLeast(1), &[T::Integer], false),
+    ("^", Plain(builtin_op), AtLeast(1), &[T::Integer], false),
+    ("<<", Plain(builtin_op), Exact(2), &[T::Integer], false),
+    (">>", Plain(builtin_op), Exact(2), &[T::Integer], false),
+    ("==", Plain(builtin_comp), Exact(2), &[], false),
+    ("!=", Plain(builtin_comp), Exact(2), &[], false),
+    (">", Plain(builtin_ord), Exact(2), &[T::Numeric], false),
+    ("<", Plain(builtin_ord), Exact(2), &[T::Numeric], false),
+    (">=", Plain(builtin_ord), Exact(2), &[T::Numeric], false),
+    ("<=", Plain(builtin_ord), Exact(2), &[T::Numeric], false),
+    ("and", Plain(builtin_logic), AtLeast(0), &[], false),
+    ("or", Plain(builtin_logic), AtLeast(0), &[], false),
+    ("not", Plain(builtin_logic), Exact(1), &[], false),
+    ("eval", Stepping(builtin_eval), Exact(1), &[T::List], false),
+    ("if", Stepping(builtin_if), Exact(3), &[T::Any, T::List], false),
+    ("do", Stepping(builtin_do), AtLeast(0), &[], true),
+    ("try", Stepping(builtin_try), AtLeast(1), &[], true),
+    ("head", Plain(builtin_head), Exact(1), &[], false),
+    ("last", Plain(builtin_last), Exact(1), &[], false),
+    ("tail", Plain(builtin_tail), Exact(1), &[], false),
+    ("list", Plain(builtin_list), AtLeast(0), &[], false),
+    ("join", Plain(builtin_join), AtLeast(0), &[], false),
+    ("def", WithEnv(builtin_var), AtLeast(1), &[T::List, T::Any], false),
+    ("=", WithEnv(builtin_var), AtLeast(1), &[T::List, T::Any], false),
+    ("\\", WithEnv(builtin_lambda), Exact(2), &[], true),
+    ("print", Plain(builtin_print), Exact(1), &[], false),
+    ("range", Plain(builtin_range), Exact(1), &[T::Number], false),
+    ("load", WithEnv(builtin_load), Exact(1), &[T::String], false),
+    ("read", Plain(builtin_read), Exact(1), &[T::String], false),
+    ("import", WithEnv(builtin_import), AtLeast(1), &[], true),
+    ("error", Plain(builtin_error), AtLeast(1), &[T::String, T::Any], false),
+    ("fun", WithEnv(builtin_fun), Exact(2), &[], true),
+    ("defmacro", WithEnv(builtin_fun), Exact(2), &[], true),
+    ("quasiquote", WithEnv(builtin_quasiquote), Exact(1), &[], true),
+    ("macroexpand", WithEnv(builtin_macroexpand), Exact(1), &[], false),
+    ("get", Plain(builtin_get), AtLeast(2), &[T::Map, T::Any], false),
+    ("assoc", Plain(builtin_assoc), AtLeast(1), &[T::Map, T::Any], false),
+    ("dissoc", Plain(builtin_dissoc), AtLeast(1), &[T::Map, T::Any], false),
+    ("keys", Plain(builtin_keys), Exact(1), &[T::Map], false),
+    ("vals", Plain(builtin_vals), Exact(1), &[T::Map], false),
+    ("has?", Plain(builtin_has), Exact(2), &[T::Map, T::Any], false),
+    ("update", WithEnv(builtin_update), Exact(3), &[T::Map, T::Any], false),
+    ("merge", Plain(builtin_merge), AtLeast(0), &[T::Map], false),
+    ("set", Plain(builtin_set), Exact(1), &[T::List], false),
+    ("set-add", Plain(builtin_set_add), AtLeast(1), &[T::Set, T::Any], false),
+    ("set-remove", Plain(builtin_set_remove), AtLeast(1), &[T::Set, T::Any], false),
+    ("member?", Plain(builtin_member), Exact(2), &[T::Set, T::Any], false),
+    ("union", Plain(builtin_set_op), AtLeast(1), &[T::Set], false),
+    ("intersection", Plain(builtin_set_op), AtLeast(1), &[T::Set], false),
+    ("difference", Plain(builtin_set_op), AtLeast(1), &[T::Set], false),
+    ("subset?", Plain(builtin_subset), Exact(2), &[T::Set], false),
+    ("chars", Plain(builtin_chars), Exact(1), &[T::String], false),
+    ("int", Plain(builtin_int), Exact(1), &[T::String], false),
+    ("sort", Plain(builtin_sort), Exact(1), &[T::List], false),
+    ("len", Plain(builtin_len), Exact(1), &[], false),
+    ("upper", Plain(builtin_case), Exact(1), &[], false),
+    ("lower", Plain(builtin_case), Exact(1), &[], false),
+    ("trim", Plain(builtin_trim), Exact(1), &[T::String], false),
+    ("trim-left", Plain(builtin_trim), Exact(1), &[T::String], false),
+    ("trim-right", Plain(builtin_trim), Exact(1), &[T::String], false),
+    ("starts-with?", Plain(builtin_affix), Exact(2), &[T::String, T::Any], false),
+    ("ends-with?", Plain(builtin_affix), Exact(2), &[T::String, T::Any], false),
+    ("index-of", Plain(builtin_index_of), Exact(2), &[T::String, T::Any], false),
+    ("replace", Plain(builtin_replace), Exact(3), &[T::String, T::Any], false),
+    ("repeat", Plain(builtin_repeat), Exact(2), &[T::Any, T::Number], false),
+    ("reverse", Plain(builtin_reverse), Exact(1), &[], false),
+    ("pad-left", Plain(builtin_pad), AtLeast(2), &[T::Any, T::Number, T::Char], false),
+    ("pad-right", Plain(builtin_pad), AtLeast(2), &[T::Any, T::Number, T::Char], false),
+    ("lines", Plain(builtin_lines_words), Exact(1), &[T::String], false),
+    ("words", Plain(builtin_lines_words), Exact(1), &[T::String], false),
+    ("string->list", Plain(builtin_chars), Exact(1), &[T::String], false),
+    ("list->string", Plain(builtin_list_to_string), Exact(1), &[T::List], false),
+    ("digit?", Plain(builtin_char_class), Exact(1), &[], false),
+    ("alpha?", Plain(builtin_char_class), Exact(1), &[], false),
+    ("whitespace?", Plain(builtin_char_class), Exact(1), &[], false),
+    ("split", Plain(builtin_split), Exact(2), &[], false),
+    ("sqrt", Plain(builtin_sqrt), Exact(1), &[T::Numeric], false),
+    ("abs", Plain(builtin_abs), Exact(1), &[T::Numeric], false),
+    ("min", Plain(builtin_min), Exact(2), &[T::Numeric], false),
+    ("max", Plain(builtin_max), Exact(2), &[T::Numeric], false),
+    ("floor", Plain(builtin_floor), Exact(1), &[T::Numeric], false),
+    ("ceil", Plain(builtin_ceil), Exact(1), &[T::Numeric], false),
+    ("round", Plain(builtin_round), Exact(1), &[T::Numeric], false),
+    ("sin", Plain(builtin_sin), Exact(1), &[T::Numeric], false),
+    ("cos", Plain(builtin_cos), Exact(1), &[T::Numeric], false),
+    ("tan", Plain(builtin_tan), Exact(1), &[T::Numeric], false),
+    ("log", Plain(builtin_log), Exact(1), &[T::Numeric], false),
+    ("exp", Plain(builtin_exp), Exact(1), &[T::Numeric], false),
+    ("truncate", Plain(builtin_truncate), Exact(1), &[T::Numeric], false),
+    ("format-float", Plain(builtin_format_float), Exact(2), &[T::Numeric, T::Number], false),
+    ("numerator", Plain(builtin_fraction_part), Exact(1), &[T::Numeric], false),
+    ("denominator", Plain(builtin_fraction_part), Exact(1), &[T::Numeric], false),
+    ("exact->inexact", Plain(builtin_exact_to_inexact), Exact(1), &[T::Numeric], false),
+    ("quot", Plain(builtin_number_theory), Exact(2), &[T::Integer], false),
+    ("rem", Plain(builtin_number_theory), Exact(2), &[T::Integer], false),
+    ("mod", Plain(builtin_number_theory), Exact(2), &[T::Integer], false),
+    ("gcd", Plain(builtin_number_theory), AtLeast(1), &[T::Integer], false),
+    ("lcm", Plain(builtin_number_theory), AtLeast(1), &[T::Integer], false),
+    ("modpow", Plain(builtin_number_theory), Exact(3), &[T::Integer], false),
+    ("modinv", Plain(builtin_number_theory), Exact(2), &[T::Integer], false),
+    ("isqrt", Plain(builtin_number_theory), Exact(1), &[T::Integer], false),
+    ("prime?", Plain(builtin_number_theory), Exact(1), &[T::Integer], false),
+    ("factorize", Plain(builtin_number_theory), Exact(1), &[T::Integer], false),
+    ("crt", Plain(builtin_number_theory), Exact(2), &[T::List], false),
+    ("regex", Plain(builtin_regex), Exact(1), &[T::String], false),
+    ("re-match", Plain(builtin_re_match), Exact(2), &[T::Any, T::String], false),
+    ("re-find-all", Plain(builtin_re_find_all), Exact(2), &[T::Any, T::String], false),
+    ("re-captures", Plain(builtin_re_captures), AtLeast(2), &[T::Any, T::String, T::Any], false),
+    ("re-replace", Plain(builtin_re_replace), Exact(3), &[T::Any, T::String], false),
+    ("re-split", Plain(builtin_re_split), Exact(2), &[T::Any, T::String], false),
+    ("format", Plain(builtin_format), AtLeast(1), &[T::String, T::Any], false),
+    ("str", Plain(builtin_str), AtLeast(0), &[], false),
+    ("repr", Plain(builtin_repr), Exact(1), &[], false),
+    ("gc", WithEnv(builtin_gc), Exact(0), &[], false),
+    ("gc-stats", WithEnv(builtin_gc_stats), Exact(0), &[], false),
+];
+
+/// The definitions every env from `setup_builtins` starts with
+const PRELUDE: &str = include_str!("../base.jl");
+
+/// An env with the builtins and the prelude (`map`, `filter`, `foldl`, ...)
 pub fn setup_builtins() -> Env {
-    let env = Env::new();
+    let env = setup_builtins_bare();
+    load_prelude(&env);
+    env
+}
 
-    // Define all builtin names using the macro
-    let builtins = builtin_list![
-        "+", "-", "*", "/", "%", "**", "^", "&", "|", "<<", ">>",
-        "==", "!=", ">", ">=", "<", "<=",
-        "and", "or", "not",
-        "head", "last", "tail", "list", "join",
-        "range", "eval", "if", "print", "load", "read",
-        "=", "def", "\\", "fun", "chars", "int", "sort", "len", "str-sub", "split",
-        "sqrt", "abs", "min", "max", "floor", "ceil", "round",
-        "sin", "cos", "tan", "log", "exp", "truncate", "error"
-    ];
-
-    for op in builtins {
-        env.insert(op.to_string(), Expr::Builtin(op.to_string()));
-    }
+/// An env with only the builtins, for embedders that bring their own
+/// definitions
+pub fn setup_builtins_bare() -> Env {
+    let env = Env::new();
+    define_builtins(&env);
+    env
+}
 
+/// A root env with the builtins and the prelude in the same interpreter as
+/// `env`, what a module is evaluated in
+pub(crate) fn setup_module(env: &Env) -> Env {
+    let env = env.new_root();
+    define_builtins(&env);
+    load_prelude(&env);
     env
 }
+
+fn load_prelude(env: &Env) {
+    io::eval_source("base.jl", PRELUDE, env, Span::default()).expect("the prelude evaluates");
+}
+
+fn define_builtins(env: &Env) {
+    for &(name, kind, arity, types, special) in BUILTINS {
+        let handler: Box<Handler> = match kind {
+            Plain(f) => Box::new(move |_, name, args, span| f(name, args, span).map(Step::Done)),
+            WithEnv(f) => {
+                Box::new(move |env, name, args, span| f(name, env, args, span).map(Step::Done))
+            }
+            Stepping(f) => Box::new(move |env, name, args, span| f(name, env, args, span)),
+        };
+        let builtin = Builtin {
+            name: name.to_string(),
+            arity,
+            types: types.to_vec(),
+            special,
+            handler,
+        };
+        env.insert(name.to_string(), Expr::Builtin(Arc::new(builtin)));
+    }
+    for def in inventory::iter::<BuiltinDef> {
+        let builtin = Builtin {
+            name: def.name.to_string(),
+            arity: def.arity,
+            types: Vec::new(),
+            special: false,
+            handler: Box::new(def.handler),
+        };
+        env.insert(def.name.to_string(), Expr::Builtin(Arc::new(builtin)));
+    }
+}
@@ -1,24 +1,35 @@
-use crate::ast::{Error, Expr};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
 
-pub fn builtin_op(sym: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
+use crate::ast::{Error, Expr, expect_arity, expect_nonempty};
+use crate::number::{self, Int, Num};
+use crate::span::Span;
+
+pub fn builtin_op(sym: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
     // Handle bitwise operations separately (only work on integers)
     if matches!(sym, "&" | "|" | "^" | "<<" | ">>") {
-        return builtin_bitwise(sym, args, line);
+        return builtin_bitwise(sym, args, span);
     }
 
     // Handle modulo and power operations
     if matches!(sym, "%" | "**") {
-        return builtin_extended_arith(sym, args, line);
+        return builtin_extended_arith(sym, args, span);
     }
 
-    //check type of first member is valid
-    if !matches!(args[0], Expr::Number(_) | Expr::Float(_) | Expr::Char(_)) {
-        return Err(Error::IncompatibleType {
-            op: sym.to_owned(),
-            expected: "Number,Float,Char".to_string(),
-            received: args[0].as_str(),
-            line,
-        });
+    expect_nonempty(sym, &args, span)?;
+
+    //check type of every member is valid
+    for arg in &args {
+        if !number::is_number(arg) && !matches!(arg, Expr::Char(_)) {
+            return Err(Error::IncompatibleType {
+                op: sym.to_owned(),
+                expected: "Number,Rational,Float,Char".to_string(),
+                received: arg.as_str(),
+                span,
+            });
+        }
     }
 
     // Check if all arguments are chars (char operations work separately)
@@ -26,197 +37,106 @@ pub fn builtin_op(sym: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error
 
     // If all chars, handle separately
     if all_chars {
-        return builtin_char_arith(sym, args, line);
+        return builtin_char_arith(sym, args, span);
     }
 
-    // Check if any argument is a float - if so, result should be float
-    let has_float = args.iter().any(|x| matches!(x, Expr::Float(_)));
-
-    // Check if any argument is a char - chars can't mix with numbers/floats
-    let has_char = args.iter().any(|x| matches!(x, Expr::Char(_)));
-
-    // For division, we need special handling to determine if result should be float
-    let should_return_float = has_float;
-
-    // Reject mixing chars with numbers/floats
-    if has_char && (has_float || args.iter().any(|x| matches!(x, Expr::Number(_)))) {
+    // Reject mixing chars with numbers
+    if args.iter().any(|x| matches!(x, Expr::Char(_))) {
         return Err(Error::InconsistentTypes {
             op: sym.to_owned(),
-            line,
+            span,
         });
     }
 
+    // Integers promote to bignums instead of overflowing and divide into
+    // rationals, floats only come out when one goes in
+    let mut nums = args.iter().map(|v| Num::from_expr(v).unwrap());
+    let mut out = nums.next().unwrap();
     if sym == "-" && args.len() == 1 {
-        match args[0] {
-            Expr::Number(n) => {
-                if should_return_float {
-                    return Ok(Expr::Float(-(n as f32)));
-                } else {
-                    return Ok(Expr::Number(-n));
-                }
-            }
-            Expr::Float(f) => return Ok(Expr::Float(-f)),
-            _ => {
-                return Err(Error::IncompatibleType {
-                    op: sym.to_owned(),
-                    expected: "Number,Float".to_string(),
-                    received: args[0].as_str(),
-                    line,
-                });
-            }
-        }
+        return Ok(out.neg().into_expr());
     }
-
-    // Handle division specially to determine if result should be float
-    if sym == "/" && !has_float {
-        // Pure integer division - check if result is exact
-        let func: fn(i32, i32) -> i32 = match sym {
-            "/" => |a, b| a / b,
+    for v in nums {
+        out = match sym {
+            "+" => out.add(v),
+            "-" => out.sub(v),
+            "*" => out.mul(v),
+            "/" => out.div(v).ok_or(Error::DivisionByZero { span })?,
             _ => panic!(),
         };
+    }
+    Ok(out.into_expr())
+}
 
-        let start = match &args[0] {
-            Expr::Number(n) => *n,
-            _ => panic!(),
-        };
-        let mut out = start;
-
-        for v in args.iter().skip(1) {
-            let Expr::Number(v) = v else {
-                panic!();
-            };
-            // check for valid op/not-zero
-            if *v == 0 {
-                return Err(Error::DivisionByZero { line });
-            }
-            out = func(out, *v);
-        }
-
-        // Check if the division was exact by multiplying back
-        let mut temp = out;
-        for v in args.iter().skip(1).rev() {
-            let Expr::Number(v) = v else {
-                panic!();
-            };
-            temp *= *v;
-        }
-
-        if temp == start {
-            Ok(Expr::Number(out))
-        } else {
-            // Not exact division, return float
-            let mut float_result = start as f32;
-            for v in args.iter().skip(1) {
-                let Expr::Number(v) = v else {
-                    panic!();
-                };
-                float_result /= *v as f32;
-            }
-            Ok(Expr::Float(float_result))
-        }
-    } else if should_return_float {
-        // Convert all arguments to float for mixed operations
-        let func: fn(f32, f32) -> f32 = match sym {
-            "+" => |a, b| a + b,
-            "-" => |a, b| a - b,
-            "*" => |a, b| a * b,
-            "/" => |a, b| a / b,
-            _ => panic!(),
-        };
-
-        let start = match &args[0] {
-            Expr::Number(n) => *n as f32,
-            Expr::Float(f) => *f,
-            _ => panic!(),
-        };
-        let mut out = start;
-
-        for v in args.iter().skip(1) {
-            let v_float = match v {
-                Expr::Number(n) => *n as f32,
-                Expr::Float(f) => *f,
-                _ => panic!(),
-            };
-            // check for valid op
-            if !(sym == "/" && v_float == 0.0) {
-                out = func(out, v_float);
-            } else {
-                return Err(Error::DivisionByZero { line });
-            }
-        }
-        Ok(Expr::Float(out))
-    } else {
-        // Pure integer operations
-        let func: fn(i32, i32) -> i32 = match sym {
-            "+" => |a, b| a + b,
-            "-" => |a, b| a - b,
-            "*" => |a, b| a * b,
-            "/" => |a, b| a / b,
-            _ => panic!(),
-        };
+// the exponent or shift amount of `sym`, which the result is computed in
+// one go for and so has to stay within reason
+fn small_amount(sym: &str, e: &Expr, span: Span) -> Result<u32, Error> {
+    match Int::from_expr(e) {
+        Some(n) if n.is_negative() => Err(Error::Domain {
+            msg: format!("{sym} amount must be non-negative"),
+            span,
+        }),
+        Some(n) => n.to_u32().ok_or_else(|| Error::Domain {
+            msg: format!("{sym} amount is too large"),
+            span,
+        }),
+        None => Err(Error::IncompatibleType {
+            op: sym.to_owned(),
+            expected: "Number".to_string(),
+            received: e.as_str(),
+            span,
+        }),
+    }
+}
 
-        let start = match &args[0] {
-            Expr::Number(n) => *n,
-            _ => panic!(),
-        };
-        let mut out = start;
+/// `**` and `<<` compute their result in one go, so results bigger than
+/// this are refused instead of running until memory runs out
+const MAX_RESULT_BITS: u64 = 1 << 20;
 
-        for v in args.iter().skip(1) {
-            let Expr::Number(v) = v else {
-                panic!();
-            };
-            // check for valid op/not-zero
-            if !(sym == "/" && *v == 0) {
-                out = func(out, *v);
-            } else {
-                return Err(Error::DivisionByZero { line });
-            }
-        }
-        Ok(Expr::Number(out))
+fn check_result_bits(sym: &str, bits: u64, span: Span) -> Result<(), Error> {
+    if bits > MAX_RESULT_BITS {
+        return Err(Error::Domain {
+            msg: format!("{sym} result would be larger than {MAX_RESULT_BITS} bits"),
+            span,
+        });
     }
+    Ok(())
 }
 
-fn builtin_bitwise(sym: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
+fn builtin_bitwise(sym: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
     if args.is_empty() {
         return Err(Error::IncompatibleType {
             op: sym.to_owned(),
             expected: "At least one argument".to_string(),
             received: "None".to_string(),
-            line,
+            span,
         });
     }
 
     // Check all arguments are numbers (bitwise ops only work on integers)
+    let mut ints = Vec::with_capacity(args.len());
     for arg in &args {
-        if !matches!(arg, Expr::Number(_)) {
+        let Some(n) = Int::from_expr(arg) else {
             return Err(Error::IncompatibleType {
                 op: sym.to_owned(),
                 expected: "Number".to_string(),
                 received: arg.as_str(),
-                line,
+                span,
             });
-        }
+        };
+        ints.push(n);
     }
-
-    let start = match &args[0] {
-        Expr::Number(n) => *n,
-        _ => panic!(),
-    };
+    let mut ints = ints.into_iter();
+    let start = ints.next().unwrap();
 
     match sym {
         "&" | "|" | "^" => {
-            let func: fn(i32, i32) -> i32 = match sym {
-                "&" => |a, b| a & b,
-                "|" => |a, b| a | b,
-                "^" => |a, b| a ^ b,
+            let func: fn(Int, Int) -> Int = match sym {
+                "&" => Int::bitand,
+                "|" => Int::bitor,
+                "^" => Int::bitxor,
                 _ => panic!(),
             };
-            let mut out = start;
-            for v in args.iter().skip(1) {
-                let Expr::Number(v) = v else { panic!() };
-                out = func(out, *v);
-            }
-            Ok(Expr::Number(out))
+            Ok(ints.fold(start, func).into_expr())
         }
         "<<" | ">>" => {
             if args.len() != 2 {
@@ -224,179 +144,298 @@ fn builtin_bitwise(sym: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Erro
                     op: sym.to_owned(),
                     expected: "Exactly 2 arguments".to_string(),
                     received: format!("{}", args.len()),
-                    line,
-                });
-            }
-
-            let Expr::Number(shift_amount) = args[1] else {
-                return Err(Error::IncompatibleType {
-                    op: sym.to_owned(),
-                    expected: "Number".to_string(),
-                    received: args[1].as_str(),
-                    line,
-                });
-            };
-
-            // Check shift amount is within valid range (0-31 for i32)
-            if shift_amount < 0 || shift_amount > 31 {
-                return Err(Error::ParseError {
-                    msg: "Shift amount must be between 0 and 31".to_string(),
-                    line,
+                    span,
                 });
             }
 
+            let shift_amount = small_amount("Shift", &args[1], span)?;
             let result = match sym {
-                "<<" => start << shift_amount,
-                ">>" => start >> shift_amount,
+                "<<" => {
+                    if !start.is_zero() {
+                        check_result_bits(sym, start.bits() + u64::from(shift_amount), span)?;
+                    }
+                    start.shl(shift_amount)
+                }
+                ">>" => start.shr(shift_amount),
                 _ => panic!(),
             };
-            Ok(Expr::Number(result))
+            Ok(result.into_expr())
         }
         _ => panic!(),
     }
 }
 
-fn builtin_extended_arith(sym: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
+fn builtin_extended_arith(sym: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
     if args.len() != 2 {
         return Err(Error::IncompatibleType {
             op: sym.to_owned(),
             expected: "Exactly 2 arguments".to_string(),
             received: format!("{}", args.len()),
-            line,
+            span,
         });
     }
 
-    // Check if any argument is a float
-    let has_float = args.iter().any(|x| matches!(x, Expr::Float(_)));
+    let base = Num::expect(&args[0], sym, span)?;
+    let exp = Num::expect(&args[1], sym, span)?;
 
     match sym {
         "%" => {
-            // Modulo only works on integers
-            if has_float {
-                return Err(Error::IncompatibleType {
-                    op: sym.to_owned(),
-                    expected: "Number".to_string(),
-                    received: "Float".to_string(),
-                    line,
-                });
-            }
-
-            let Expr::Number(a) = args[0] else {
-                return Err(Error::IncompatibleType {
-                    op: sym.to_owned(),
-                    expected: "Number".to_string(),
-                    received: args[0].as_str(),
-                    line,
-                });
-            };
-
-            let Expr::Number(b) = args[1] else {
-                return Err(Error::IncompatibleType {
-                    op: sym.to_owned(),
-                    expected: "Number".to_string(),
-                    received: args[1].as_str(),
-                    line,
-                });
+            // Modulo is exact, so it takes integers and rationals
+            let exact = |n: Num| match n {
+                Num::Int(n) => Some(BigRational::from_integer(n.big())),
+                Num::Ratio(r) => Some(r),
+                Num::Float(_) => None,
             };
-
-            if b == 0 {
-                return Err(Error::DivisionByZero { line });
+            match (base, exp) {
+                (Num::Int(a), Num::Int(b)) => {
+                    if b.is_zero() {
+                        return Err(Error::DivisionByZero { span });
+                    }
+                    Ok(a.rem(b).into_expr())
+                }
+                (a, b) => {
+                    let (Some(a), Some(b)) = (exact(a), exact(b)) else {
+                        let other = args.iter().find(|a| matches!(a, Expr::Float(_))).unwrap();
+                        return Err(Error::IncompatibleType {
+                            op: sym.to_owned(),
+                            expected: "Number or Rational".to_string(),
+                            received: other.as_str(),
+                            span,
+                        });
+                    };
+                    if b.is_zero() {
+                        return Err(Error::DivisionByZero { span });
+                    }
+                    // like for integers the result takes the sign of `a`
+                    let q = (&a / &b).trunc();
+                    Ok(Num::Ratio(a - b * q).into_expr())
+                }
             }
-            Ok(Expr::Number(a % b))
         }
         "**" => {
             // Power operation - supports mixed types
-            let a = match &args[0] {
-                Expr::Number(n) => *n as f32,
-                Expr::Float(f) => *f,
-                _ => {
-                    return Err(Error::IncompatibleType {
-                        op: sym.to_owned(),
-                        expected: "Number or Float".to_string(),
-                        received: args[0].as_str(),
-                        line,
-                    });
-                }
+            let Num::Int(exp) = exp else {
+                // For fractional exponents, we need to use powf
+                return Ok(Expr::Float(base.to_f64().powf(exp.to_f64())));
             };
+            if let Num::Float(f) = base {
+                return Ok(Expr::Float(f.powf(exp.to_f64())));
+            }
 
-            let b = match &args[1] {
-                Expr::Number(n) => *n,
-                Expr::Float(f) => {
-                    // For float exponents, we need to use powf instead of powi
-                    let result = a.powf(*f);
-                    return Ok(Expr::Float(result));
-                }
-                _ => {
-                    return Err(Error::IncompatibleType {
-                        op: sym.to_owned(),
-                        expected: "Number or Float".to_string(),
-                        received: args[1].as_str(),
-                        line,
-                    });
-                }
+            // powers of integers and rationals are exact, a negative power
+            // is a power of the inverse
+            let (base, exp) = if exp.is_negative() {
+                let inverse = Num::Int(Int::Small(1)).div(base).ok_or(Error::Domain {
+                    msg: "0 has no negative powers".to_string(),
+                    span,
+                })?;
+                (inverse, exp.neg())
+            } else {
+                (base, exp)
             };
-
-            if b < 0 {
-                return Err(Error::ParseError {
-                    msg: "Power exponent must be non-negative".to_string(),
-                    line,
-                });
+            let b = small_amount("Power", &exp.into_expr(), span)?;
+            let bits = match &base {
+                Num::Int(n) => n.bits(),
+                Num::Ratio(r) => r.numer().bits().max(r.denom().bits()),
+                Num::Float(_) => 0,
+            };
+            // 0, 1 and -1 stay that size whatever the power
+            if bits > 1 {
+                check_result_bits(sym, bits.saturating_mul(u64::from(b)), span)?;
             }
+            Ok(base.pow(b).into_expr())
+        }
+        _ => panic!(),
+    }
+}
+
+// the args of a number theory builtin, all of which have to be integers
+fn integer_args(sym: &str, args: &[Expr], n: usize, span: Span) -> Result<Vec<BigInt>, Error> {
+    expect_arity(sym, args, n, span)?;
+    args.iter()
+        .map(|arg| match Int::from_expr(arg) {
+            Some(i) => Ok(i.big()),
+            None => Err(Error::IncompatibleType {
+                op: sym.to_owned(),
+                expected: "Number".to_string(),
+                received: arg.as_str(),
+                span,
+            }),
+        })
+        .collect()
+}
 
-            let result = a.powi(b);
-            if has_float {
-                Ok(Expr::Float(result))
+fn domain_error(msg: &str, span: Span) -> Error {
+    Error::Domain {
+        msg: msg.to_string(),
+        span,
+    }
+}
+
+/// `quot`, `rem`, `mod`, `gcd`, `lcm`, `modpow`, `modinv`, `isqrt`, `prime?`,
+/// `factorize` and `crt`, on integers of any size
+pub fn builtin_number_theory(sym: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let int = |n: BigInt| Int::Big(n).into_expr();
+    match sym {
+        "quot" | "rem" | "mod" => {
+            let [a, b] = <[BigInt; 2]>::try_from(integer_args(sym, &args, 2, span)?).unwrap();
+            if b.is_zero() {
+                return Err(Error::DivisionByZero { span });
+            }
+            Ok(int(match sym {
+                "quot" => a / b,
+                "rem" => a % b,
+                // the result takes the sign of the divisor
+                _ => a.mod_floor(&b),
+            }))
+        }
+        "gcd" | "lcm" => {
+            expect_nonempty(sym, &args, span)?;
+            let ns = integer_args(sym, &args, args.len(), span)?;
+            let func: fn(&BigInt, &BigInt) -> BigInt = match sym {
+                "gcd" => BigInt::gcd,
+                _ => BigInt::lcm,
+            };
+            let mut ns = ns.into_iter();
+            let first = ns.next().unwrap().abs();
+            Ok(int(ns.fold(first, |acc, n| func(&acc, &n))))
+        }
+        "modpow" => {
+            let [base, exp, m] =
+                <[BigInt; 3]>::try_from(integer_args(sym, &args, 3, span)?).unwrap();
+            if m.is_zero() {
+                return Err(Error::DivisionByZero { span });
+            }
+            // results are in [0, |m|) whatever the sign of m
+            let m = m.abs();
+            // a negative power is a power of the inverse
+            let base = if exp.is_negative() {
+                base.modinv(&m).ok_or_else(|| {
+                    domain_error("modpow base has no inverse for a negative exponent", span)
+                })?
             } else {
-                // Check if result is close to an integer
-                if (result - result.round()).abs() < 0.0001 {
-                    Ok(Expr::Number(result.round() as i32))
-                } else {
-                    Ok(Expr::Float(result))
-                }
+                base
+            };
+            Ok(int(base.modpow(&exp.abs(), &m)))
+        }
+        "modinv" => {
+            let [a, m] = <[BigInt; 2]>::try_from(integer_args(sym, &args, 2, span)?).unwrap();
+            if m.is_zero() {
+                return Err(Error::DivisionByZero { span });
+            }
+            let m = m.abs();
+            match a.modinv(&m) {
+                Some(inv) => Ok(int(inv.mod_floor(&m))),
+                None => Err(domain_error(
+                    "modinv argument is not coprime to the modulus",
+                    span,
+                )),
             }
         }
+        "isqrt" => {
+            let [n] = <[BigInt; 1]>::try_from(integer_args(sym, &args, 1, span)?).unwrap();
+            if n.is_negative() {
+                return Err(domain_error("isqrt argument must be non-negative", span));
+            }
+            Ok(int(n.sqrt()))
+        }
+        "prime?" => {
+            let [n] = <[BigInt; 1]>::try_from(integer_args(sym, &args, 1, span)?).unwrap();
+            Ok(Expr::Bool(number::is_prime(&n)))
+        }
+        "factorize" => {
+            let [n] = <[BigInt; 1]>::try_from(integer_args(sym, &args, 1, span)?).unwrap();
+            if !n.is_positive() {
+                return Err(domain_error("factorize argument must be positive", span));
+            }
+            Ok(Expr::List(
+                number::factorize(n).into_iter().map(int).collect(),
+            ))
+        }
+        "crt" => builtin_crt(sym, args, span),
         _ => panic!(),
     }
 }
 
-fn builtin_char_arith(sym: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    if sym == "-" && args.len() == 1 {
-        match args[0] {
-            Expr::Char(c) => return Ok(Expr::Number(-(c as i32))),
-            _ => panic!(),
+/// `(crt [r1 r2 ...] [m1 m2 ...])`, the smallest non-negative x with
+/// x = ri mod mi for every i. The moduli don't have to be coprime, nil when
+/// the congruences contradict each other.
+fn builtin_crt(sym: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(sym, &args, 2, span)?;
+    let mut args = args.into_iter();
+    let residues = args.next().unwrap().into_list(sym, span)?;
+    let moduli = args.next().unwrap().into_list(sym, span)?;
+    if residues.len() != moduli.len() {
+        return Err(Error::WrongAmountOfArgs {
+            func: sym.to_owned(),
+            expected: residues.len(),
+            received: moduli.len(),
+            span,
+        });
+    }
+    let residues = integer_args(sym, &residues, residues.len(), span)?;
+    let moduli = integer_args(sym, &moduli, moduli.len(), span)?;
+
+    // x solves every congruence so far, modulo m
+    let mut x = BigInt::zero();
+    let mut m = BigInt::from(1);
+    for (r, mi) in residues.into_iter().zip(moduli) {
+        if mi.is_zero() {
+            return Err(Error::DivisionByZero { span });
         }
+        let mi = mi.abs();
+        let g = m.gcd(&mi);
+        let diff = r - &x;
+        if !(&diff % &g).is_zero() {
+            return Ok(Expr::Nil);
+        }
+        // x + m*t = r (mod mi), so t = diff/g * (m/g)^-1 (mod mi/g)
+        let step = &mi / &g;
+        let t = match (&m / &g).modinv(&step) {
+            Some(inv) => (diff / &g * inv).mod_floor(&step),
+            None => BigInt::zero(),
+        };
+        x += &m * t;
+        m = m.lcm(&mi);
+        x = x.mod_floor(&m);
     }
+    Ok(Int::Big(x).into_expr())
+}
 
-    let func: fn(i32, i32) -> i32 = match sym {
-        "+" => |a, b| a + b,
-        "-" => |a, b| a - b,
-        "*" => |a, b| a * b,
-        "/" => |a, b| a / b,
+fn builtin_char_arith(sym: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let code = |e: &Expr| match e {
+        Expr::Char(c) => Int::Small(*c as i64),
         _ => panic!(),
     };
 
-    let start = match &args[0] {
-        Expr::Char(c) => *c as i32,
+    if sym == "-" && args.len() == 1 {
+        return Ok(code(&args[0]).neg().into_expr());
+    }
+
+    // Chars compute on their codes, which like any integer promote to a
+    // bignum instead of overflowing
+    let func: fn(Int, Int) -> Int = match sym {
+        "+" => Int::add,
+        "-" => Int::sub,
+        "*" => Int::mul,
+        "/" => Int::div,
         _ => panic!(),
     };
-    let mut out = start;
 
+    let mut out = code(&args[0]);
     for v in args.iter().skip(1) {
-        let Expr::Char(c) = v else {
-            panic!();
-        };
-        // check for valid op/not-zero
-        if !(sym == "/" && *c as i32 == 0) {
-            out = func(out, *c as i32);
-        } else {
-            return Err(Error::DivisionByZero { line });
+        let c = code(v);
+        if sym == "/" && c.is_zero() {
+            return Err(Error::DivisionByZero { span });
         }
+        out = func(out, c);
     }
 
     // If result is in valid char range and we're doing addition/subtraction, return char
-    if matches!(sym, "+" | "-") && out >= 0 && out <= 255 {
-        Ok(Expr::Char(out as u8 as char))
-    } else {
-        Ok(Expr::Number(out))
+    match out.into_expr() {
+        Expr::Number(n) if matches!(sym, "+" | "-") && (0..=255).contains(&n) => {
+            Ok(Expr::Char(n as u8 as char))
+        }
+        out => Ok(out),
     }
 }
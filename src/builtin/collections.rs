@@ -1,32 +1,29 @@
 use crate::ast::{Error, Expr, expect_arity, expect_nonempty};
 use crate::builtin::macros::single_list_op;
+use crate::cells::Cells;
+use crate::span::Span;
 
-pub fn builtin_head(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    single_list_op!(args, func, line, |ls: Vec<Expr>| Ok(Expr::List(vec![
-        ls.into_iter().next().unwrap()
-    ])))
+pub fn builtin_head(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    single_list_op!(args, func, span, |ls: Cells| Ok(Expr::List(
+        vec![ls[0].clone()].into()
+    )))
 }
 
-pub fn builtin_last(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    single_list_op!(args, func, line, |ls: Vec<Expr>| Ok(ls
-        .into_iter()
-        .last()
-        .unwrap()))
+pub fn builtin_last(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    single_list_op!(args, func, span, |ls: Cells| Ok(ls.last().unwrap().clone()))
 }
 
-pub fn builtin_tail(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    single_list_op!(args, func, line, |ls: Vec<Expr>| Ok(Expr::List(
-        ls.into_iter().skip(1).collect()
-    )))
+pub fn builtin_tail(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    single_list_op!(args, func, span, |ls: Cells| Ok(Expr::List(ls.tail())))
 }
 
-pub fn builtin_list(_func: &str, args: Vec<Expr>, _line: usize) -> Result<Expr, Error> {
-    Ok(Expr::List(args))
+pub fn builtin_list(_func: &str, args: Vec<Expr>, _span: Span) -> Result<Expr, Error> {
+    Ok(Expr::List(args.into()))
 }
 
-pub fn builtin_join(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
+pub fn builtin_join(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
     if args.is_empty() {
-        return Ok(Expr::List(Vec::new()));
+        return Ok(Expr::List(Cells::new()));
     }
 
     // Check if all arguments are strings
@@ -37,7 +34,7 @@ pub fn builtin_join(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Er
         // String concatenation mode
         let mut result = String::new();
         for arg in args {
-            let s = arg.into_string(func, line)?;
+            let s = arg.into_string(func, span)?;
             result.push_str(&s);
         }
         Ok(Expr::String(result))
@@ -45,56 +42,61 @@ pub fn builtin_join(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Er
         // List concatenation mode (original behavior)
         let mut out = Vec::new();
         for q in args {
-            out.extend(q.into_list(func, line)?);
+            out.extend(q.into_list(func, span)?);
         }
-        Ok(Expr::List(out))
+        Ok(Expr::List(out.into()))
     } else {
         // Mixed types - error
         Err(Error::InconsistentTypes {
             op: func.to_string(),
-            line,
+            span,
         })
     }
 }
 
-pub fn builtin_len(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
+pub fn builtin_len(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
     let arg = args.into_iter().next().unwrap();
 
     match arg {
-        Expr::String(s) => Ok(Expr::Number(s.chars().count() as i32)),
-        Expr::List(q) => Ok(Expr::Number(q.len() as i32)),
-        Expr::Sexpr(s) => Ok(Expr::Number(s.len() as i32)),
+        Expr::String(s) => Ok(Expr::Number(s.chars().count() as i64)),
+        Expr::List(q) => Ok(Expr::Number(q.len() as i64)),
+        Expr::Sexpr(s) => Ok(Expr::Number(s.len() as i64)),
+        Expr::Map(m) => Ok(Expr::Number(m.len() as i64)),
+        Expr::Set(s) => Ok(Expr::Number(s.len() as i64)),
         _ => Err(Error::IncompatibleType {
             op: func.to_string(),
-            expected: "String, List, or Sexpr".to_string(),
+            expected: "String, List, Sexpr, Map or Set".to_string(),
             received: arg.as_str(),
-            line,
+            span,
         }),
     }
 }
 
-pub fn builtin_split(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 2, line)?;
+pub fn builtin_split(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
     let delimiter = args[0].clone();
     let input = args[1].clone();
 
     match input {
         Expr::String(s) => {
-            let Expr::Char(delimiter_char) = delimiter else {
-                return Err(Error::IncompatibleType {
-                    op: func.to_string(),
-                    expected: "Char".to_string(),
-                    received: delimiter.as_str(),
-                    line,
-                });
+            let parts: Vec<&str> = match &delimiter {
+                Expr::Char(c) => s.split(*c).collect(),
+                Expr::String(d) if !d.is_empty() => s.split(d.as_str()).collect(),
+                _ => {
+                    return Err(Error::IncompatibleType {
+                        op: func.to_string(),
+                        expected: "Char or non-empty String".to_string(),
+                        received: delimiter.as_str(),
+                        span,
+                    });
+                }
             };
-
-            let parts: Vec<Expr> = s
-                .split(delimiter_char)
+            let parts: Vec<Expr> = parts
+                .into_iter()
                 .map(|part| Expr::String(part.to_string()))
                 .collect();
-            Ok(Expr::List(parts))
+            Ok(Expr::List(parts.into()))
         }
         Expr::List(q) => {
             let mut result = Vec::new();
@@ -102,21 +104,21 @@ pub fn builtin_split(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, E
 
             for item in q {
                 if item == delimiter {
-                    result.push(Expr::List(current_chunk.clone()));
+                    result.push(Expr::List(current_chunk.clone().into()));
                     current_chunk.clear();
                 } else {
                     current_chunk.push(item);
                 }
             }
-            result.push(Expr::List(current_chunk));
+            result.push(Expr::List(current_chunk.into()));
 
-            Ok(Expr::List(result))
+            Ok(Expr::List(result.into()))
         }
         _ => Err(Error::IncompatibleType {
             op: func.to_string(),
             expected: "String or List".to_string(),
             received: input.as_str(),
-            line,
+            span,
         }),
     }
 }
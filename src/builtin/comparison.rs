@@ -1,74 +1,60 @@
 use crate::ast::{Error, Expr, expect_arity};
-use crate::builtin::macros::two_number_op;
+use crate::number;
+use crate::span::Span;
 
-pub fn builtin_comp(func: &str, mut args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 2, line)?;
+pub fn builtin_comp(func: &str, mut args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
     let a = args.remove(0);
     let b = args.remove(0);
 
+    // numbers of different kinds are equal when their values are, like `<=`
+    // has them, anything else is compared structurally
+    let eq = match number::compare(&a, &b) {
+        Some(o) => o.is_eq(),
+        None if number::is_number(&a) && number::is_number(&b) => false,
+        None => a == b,
+    };
     let o = match func {
-        "==" => {
-            if a == b {
-                1
-            } else {
-                0
-            }
-        }
-        "!=" => {
-            if a == b {
-                0
-            } else {
-                1
-            }
-        }
+        "==" => eq,
+        "!=" => !eq,
         _ => panic!(),
     };
-    Ok(Expr::Number(o))
+    Ok(Expr::Bool(o))
 }
 
-pub fn builtin_logic(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
+pub fn builtin_logic(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
     match func {
         "not" => {
-            expect_arity(func, &args, 1, line)?;
-            let arg = args[0].clone().into_number(func, line)?;
-            Ok(Expr::Number(if arg == 0 { 1 } else { 0 }))
-        }
-        "and" => {
-            if args.is_empty() {
-                return Ok(Expr::Number(1));
-            }
-            for arg in &args {
-                let val = arg.clone().into_number(func, line)?;
-                if val == 0 {
-                    return Ok(Expr::Number(0));
-                }
-            }
-            Ok(Expr::Number(1))
-        }
-        "or" => {
-            if args.is_empty() {
-                return Ok(Expr::Number(0));
-            }
-            for arg in &args {
-                let val = arg.clone().into_number(func, line)?;
-                if val != 0 {
-                    return Ok(Expr::Number(1));
-                }
-            }
-            Ok(Expr::Number(0))
+            expect_arity(func, &args, 1, span)?;
+            Ok(Expr::Bool(!args[0].is_truthy()))
         }
+        "and" => Ok(Expr::Bool(args.iter().all(Expr::is_truthy))),
+        "or" => Ok(Expr::Bool(args.iter().any(Expr::is_truthy))),
         _ => panic!(),
     }
 }
 
-pub fn builtin_ord(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    two_number_op!(args, func, line, |l, r| {
-        match func {
-            ">" => l > r,
-            "<" => l < r,
-            ">=" => l >= r,
-            "<=" => l <= r,
-            _ => panic!(),
+pub fn builtin_ord(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    for arg in &args {
+        if !number::is_number(arg) {
+            return Err(Error::IncompatibleType {
+                op: func.to_string(),
+                expected: "Number, Rational or Float".to_string(),
+                received: arg.as_str(),
+                span,
+            });
         }
-    })
+    }
+    // NaN is in no order with anything
+    let Some(o) = number::compare(&args[0], &args[1]) else {
+        return Ok(Expr::Bool(false));
+    };
+    Ok(Expr::Bool(match func {
+        ">" => o.is_gt(),
+        "<" => o.is_lt(),
+        ">=" => o.is_ge(),
+        "<=" => o.is_le(),
+        _ => panic!(),
+    }))
 }
@@ -1,37 +1,48 @@
-use crate::ast::{Error, Expr, expect_arity, expect_nonempty};
+use crate::ast::{Error, Expr, Step, expect_arity, expect_nonempty};
+use crate::cells::Cells;
 use crate::env::Env;
+use crate::span::Span;
+use std::sync::Arc;
 
+/// Evaluate a list of forms in order, leaving the last one in tail position
 #[inline(always)]
-pub fn builtin_eval(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
-    let list = args.into_iter().next().unwrap().into_list(func, line)?;
+pub fn builtin_eval(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Step, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let list = args.into_iter().next().unwrap().into_list(func, span)?;
 
-    if list.is_empty() {
-        // Empty expression
-        Ok(Expr::Sexpr(Vec::new()))
-    } else {
-        // Always evaluate expressions sequentially, return last result
-        let mut result = Expr::Sexpr(Vec::new());
-        for expr in list {
-            // For each expression, if it's a List, convert to Sexpr for evaluation
-            match expr {
-                Expr::List(inner) => {
-                    result = Expr::Sexpr(inner).eval(e, line)?;
-                }
-                _ => {
-                    result = expr.eval(e, line)?;
-                }
-            }
-        }
-        Ok(result)
+    // Empty expression
+    let Some((last, init)) = list.split_last() else {
+        return Ok(Step::Done(Expr::Sexpr(Cells::new())));
+    };
+
+    // For each expression, if it's a List, convert to Sexpr for evaluation
+    let as_form = |expr: &Expr| match expr {
+        Expr::List(inner) => Expr::Sexpr(inner.clone()),
+        _ => expr.clone(),
+    };
+    for expr in init {
+        as_form(expr).eval(e, span)?;
     }
+    Ok(Step::Eval(as_form(last), e.clone()))
 }
 
-pub fn builtin_var(e: Env, func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_nonempty(func, &args, line)?;
+/// Evaluate each (unevaluated) argument in order, the last one in tail position
+pub fn builtin_do(_func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Step, Error> {
+    let Some((last, init)) = args.split_last() else {
+        return Ok(Step::Done(Expr::List(Cells::new())));
+    };
+
+    for expr in init {
+        expr.clone().eval(e, span)?;
+    }
+    Ok(Step::Eval(last.clone(), e.clone()))
+}
+
+pub fn builtin_var(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
 
     // check symbols
-    let symbols = args[0].clone().into_list(func, line)?;
+    let symbols = args[0].clone().into_list(func, span)?;
     for s in symbols.iter() {
         if let Expr::Symbol(_) = s {
             // all gud
@@ -40,27 +51,27 @@ pub fn builtin_var(e: Env, func: &str, args: Vec<Expr>, line: usize) -> Result<E
                 op: func.to_string(),
                 expected: "Symbol".to_string(),
                 received: s.as_str(),
-                line,
+                span,
             });
         }
     }
 
     // match length
-    expect_arity(func, &args, symbols.len() + 1, line)?;
+    expect_arity(func, &args, symbols.len() + 1, span)?;
 
     // Evaluate the values before storing them
     let mut values = Vec::new();
     for ar in args.into_iter().skip(1) {
-        values.push(ar.eval(e, line)?);
+        values.push(ar.eval(e, span)?);
     }
 
-    for (sy, ar) in symbols.into_iter().zip(values.into_iter()) {
+    for (sy, ar) in symbols.into_iter().zip(values) {
         let Expr::Symbol(sy) = sy else {
             return Err(Error::IncompatibleType {
                 op: func.to_string(),
                 expected: "Symbol".to_string(),
                 received: sy.as_str(),
-                line,
+                span,
             });
         };
         match func {
@@ -77,22 +88,22 @@ pub fn builtin_var(e: Env, func: &str, args: Vec<Expr>, line: usize) -> Result<E
         }
     }
 
-    Ok(Expr::Sexpr(Vec::new()))
+    Ok(Expr::Sexpr(Cells::new()))
 }
 
-pub fn builtin_lambda(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 2, line)?;
+pub fn builtin_lambda(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
 
     // check arg types - accept both List and Sexpr for formals
     let formals = match args.first().unwrap() {
-        Expr::List(formals) => formals.to_vec(),
-        Expr::Sexpr(sexpr) => sexpr.to_vec(),
+        Expr::List(formals) => formals.clone(),
+        Expr::Sexpr(sexpr) => sexpr.clone(),
         _ => {
             return Err(Error::IncompatibleType {
                 op: func.to_string(),
                 expected: "List or Sexpr".to_string(),
                 received: args[0].as_str(),
-                line,
+                span,
             });
         }
     };
@@ -104,21 +115,21 @@ pub fn builtin_lambda(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Resul
                 op: func.to_string(),
                 expected: "Symbol".to_string(),
                 received: f.as_str(),
-                line,
+                span,
             });
         };
     }
 
     // check body is a list or sexpr
     let body = match args.get(1).unwrap() {
-        Expr::List(body) => body.to_vec(),
-        Expr::Sexpr(sexpr) => sexpr.to_vec(),
+        Expr::List(body) => body.clone(),
+        Expr::Sexpr(sexpr) => sexpr.clone(),
         _ => {
             return Err(Error::IncompatibleType {
                 op: func.to_string(),
                 expected: "List or Sexpr".to_string(),
                 received: args[1].as_str(),
-                line,
+                span,
             });
         }
     };
@@ -128,38 +139,37 @@ pub fn builtin_lambda(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Resul
 
     Ok(Expr::Lambda {
         env: lambda_env,
-        formals: formals,
-        body: Box::new(Expr::List(body)), // shouldnt create new mem i think
+        formals,
+        body: Arc::new(Expr::List(body)),
     })
 }
 
 #[inline(always)]
-pub fn builtin_if(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 3, line)?;
-    let cond = args[0].clone().into_number(func, line)?;
-    let tbr = Expr::Sexpr(args[1].clone().into_list(func, line)?);
-    let fbr = Expr::Sexpr(args[2].clone().into_list(func, line)?);
-
-    if cond != 0 {
-        tbr.eval(e, line)
+pub fn builtin_if(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Step, Error> {
+    expect_arity(func, &args, 3, span)?;
+    let tbr = Expr::Sexpr(args[1].clone().into_list(func, span)?);
+    let fbr = Expr::Sexpr(args[2].clone().into_list(func, span)?);
+
+    if args[0].is_truthy() {
+        Ok(Step::Eval(tbr, e.clone()))
     } else {
-        fbr.eval(e, line)
+        Ok(Step::Eval(fbr, e.clone()))
     }
 }
 
-pub fn builtin_fun(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 2, line)?;
+pub fn builtin_fun(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
 
     // Get the name-and-params list
     let name_and_params = match args.first().unwrap() {
-        Expr::List(list) => list.to_vec(),
-        Expr::Sexpr(sexpr) => sexpr.to_vec(),
+        Expr::List(list) => list.clone(),
+        Expr::Sexpr(sexpr) => sexpr.clone(),
         _ => {
             return Err(Error::IncompatibleType {
                 op: func.to_string(),
                 expected: "List or Sexpr".to_string(),
                 received: args[0].as_str(),
-                line,
+                span,
             });
         }
     };
@@ -169,7 +179,7 @@ pub fn builtin_fun(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Result<E
             func: func.to_string(),
             expected: 1,
             received: 0,
-            line,
+            span,
         });
     }
 
@@ -181,24 +191,29 @@ pub fn builtin_fun(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Result<E
                 op: func.to_string(),
                 expected: "Symbol".to_string(),
                 received: name_and_params[0].as_str(),
-                line,
+                span,
             });
         }
     };
 
-    let params = name_and_params[1..].to_vec();
+    let params = name_and_params.tail();
     let body = args.get(1).unwrap().clone();
 
     // Create the lambda
     let lambda = Expr::Lambda {
         env: Env::child(e),
         formals: params,
-        body: Box::new(body),
+        body: Arc::new(body),
     };
 
-    // Define the function
+    // Define the function, or the macro running it
+    let value = match func {
+        "fun" => lambda,
+        "defmacro" => Expr::Macro(Arc::new(lambda)),
+        _ => panic!(),
+    };
     let root = e.root();
-    root.insert(func_name, lambda);
+    root.insert(func_name, value);
 
-    Ok(Expr::Sexpr(Vec::new()))
+    Ok(Expr::Sexpr(Cells::new()))
 }
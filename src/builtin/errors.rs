@@ -0,0 +1,108 @@
+use crate::ast::{Error, Expr, Map, Step, expect_nonempty};
+use crate::cells::Cells;
+use crate::env::Env;
+use crate::span::Span;
+use std::sync::Arc;
+
+/// `(error msg)` or `(error msg payload)`, raises an error the script can
+/// catch with `try`, `payload` is handed to the catch clause as it is
+pub fn builtin_error(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    if args.len() != 1 && args.len() != 2 {
+        return Err(Error::WrongAmountOfArgs {
+            func: func.to_string(),
+            expected: 1,
+            received: args.len(),
+            span,
+        });
+    }
+    let mut args = args.into_iter();
+    let msg = args.next().unwrap().into_string(func, span)?;
+    Err(Error::User {
+        msg,
+        payload: args.next().map(Box::new),
+        span,
+    })
+}
+
+// the value a catch clause binds, a map of what went wrong and where
+fn error_value(err: Error) -> Expr {
+    let key = |k: &str| Expr::String(k.to_string());
+    let root = err.root();
+    let at = root.span();
+    let mut map = Map::new();
+    map.insert(key("kind"), key(err.kind()));
+    map.insert(key("message"), Expr::String(err.message()));
+    map.insert(key("file"), Expr::String(at.file.name()));
+    map.insert(key("line"), Expr::Number(at.line.into()));
+    map.insert(key("col"), Expr::Number(at.col.into()));
+    let payload = match root {
+        Error::User {
+            payload: Some(p), ..
+        } => (**p).clone(),
+        _ => Expr::Nil,
+    };
+    map.insert(key("payload"), payload);
+    Expr::Map(Arc::new(map))
+}
+
+// the clause `form` is, if it is `(catch ...)` or `(finally ...)`
+fn clause<'a>(form: &'a Expr, name: &str) -> Option<&'a [Expr]> {
+    match form {
+        Expr::Sexpr(cells) if matches!(cells.first(), Some(Expr::Symbol(s)) if s == name) => {
+            Some(&cells[1..])
+        }
+        _ => None,
+    }
+}
+
+// evaluate `forms` in order, the value of the last or an empty list
+fn eval_all(forms: &[Expr], env: &Env, span: Span) -> Result<Expr, Error> {
+    let mut last = Expr::Sexpr(Cells::new());
+    for form in forms {
+        last = form.clone().eval(env, span)?;
+    }
+    Ok(last)
+}
+
+/// `(try body... (catch e handler...) (finally cleanup...))`, the value of
+/// `body`, or if it raises an error, of `handler` with `e` bound to a map of
+/// the error's kind, message, file, line, col and payload. `cleanup` runs
+/// either way and an error it raises wins. Both clauses are optional.
+pub fn builtin_try(func: &str, env: &Env, args: Vec<Expr>, span: Span) -> Result<Step, Error> {
+    expect_nonempty(func, &args, span)?;
+    let mut body = &args[..];
+    let mut finally = None;
+    if let Some((last, init)) = body.split_last()
+        && let Some(forms) = clause(last, "finally")
+    {
+        finally = Some(forms);
+        body = init;
+    }
+    let mut catch = None;
+    if let Some((last, init)) = body.split_last()
+        && let Some(forms) = clause(last, "catch")
+    {
+        let Some((name, handler)) = forms.split_first() else {
+            return Err(Error::WrongAmountOfArgs {
+                func: "catch".to_string(),
+                expected: 1,
+                received: 0,
+                span,
+            });
+        };
+        catch = Some((name.clone().into_symbol("catch", span)?, handler));
+        body = init;
+    }
+
+    let mut result = eval_all(body, env, span);
+    if let (Err(_), Some((name, handler))) = (&result, catch) {
+        let err = result.unwrap_err();
+        let scope = Env::child(env);
+        scope.insert(name, error_value(err));
+        result = eval_all(handler, &scope, span);
+    }
+    if let Some(cleanup) = finally {
+        eval_all(cleanup, env, span)?;
+    }
+    result.map(Step::Done)
+}
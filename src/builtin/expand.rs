@@ -0,0 +1,91 @@
+use crate::ast::{Error, Expr, expand_macro, expect_arity};
+use crate::cells::Cells;
+use crate::env::Env;
+use crate::span::Span;
+use crate::trace::CallSite;
+
+/// Fill in a template, evaluating what's unquoted inside of it
+pub fn builtin_quasiquote(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    quasi(&args[0], e, 1, span)
+}
+
+// `(sym x)` for one of the quoting forms
+fn quoting(cells: &Cells) -> Option<(&str, &Expr)> {
+    match &cells[..] {
+        [Expr::Symbol(sym), x]
+            if matches!(sym.as_str(), "quasiquote" | "unquote" | "unquote-splicing") =>
+        {
+            Some((sym.as_str(), x))
+        }
+        _ => None,
+    }
+}
+
+// `depth` counts the quasiquotes around `template`, only unquotes that
+// close the outermost one are evaluated
+fn quasi(template: &Expr, e: &Env, depth: usize, span: Span) -> Result<Expr, Error> {
+    let (cells, rebuild): (&Cells, fn(Cells) -> Expr) = match template {
+        Expr::Sexpr(cells) => (cells, Expr::Sexpr),
+        Expr::List(cells) => (cells, Expr::List),
+        _ => return Ok(template.clone()),
+    };
+
+    match quoting(cells) {
+        Some(("unquote" | "unquote-splicing", x)) if depth == 1 => return x.clone().eval(e, span),
+        Some((sym, x)) => {
+            let depth = if sym == "quasiquote" {
+                depth + 1
+            } else {
+                depth - 1
+            };
+            let x = quasi(x, e, depth, span)?;
+            return Ok(rebuild(vec![Expr::Symbol(sym.to_string()), x].into()));
+        }
+        None => {}
+    }
+
+    let mut out = Vec::with_capacity(cells.len());
+    for item in cells {
+        if let Expr::Sexpr(inner) = item
+            && let Some(("unquote-splicing", x)) = quoting(inner)
+            && depth == 1
+        {
+            out.extend(
+                x.clone()
+                    .eval(e, span)?
+                    .into_cells("unquote-splicing", span)?,
+            );
+        } else {
+            out.push(quasi(item, e, depth, span)?);
+        }
+    }
+    Ok(rebuild(out.into()))
+}
+
+/// Expand the macro call `form` until it's no longer a macro call
+pub fn builtin_macroexpand(
+    func: &str,
+    e: &Env,
+    args: Vec<Expr>,
+    span: Span,
+) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let mut form = args.into_iter().next().unwrap();
+    loop {
+        let (Expr::Sexpr(cells) | Expr::List(cells)) = &form else {
+            return Ok(form);
+        };
+        let Some(Expr::Symbol(sym)) = cells.first() else {
+            return Ok(form);
+        };
+        let Some(op @ Expr::Macro(_)) = e.get(sym) else {
+            return Ok(form);
+        };
+        let site = CallSite {
+            form: cells.clone(),
+            span,
+        };
+        form = expand_macro(op, cells[1..].to_vec(), &site)?;
+    }
+}
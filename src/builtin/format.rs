@@ -0,0 +1,256 @@
+use crate::ast::{Error, Expr, expect_arity, expect_nonempty};
+use crate::number::{self, Int, Num};
+use crate::span::Span;
+use num_bigint::{BigInt, Sign};
+use std::iter::Peekable;
+use std::str::Chars;
+
+// how a value is laid out in a `{...}` placeholder, the same spec as Rust's
+// `format!`: [[fill]align][sign][#][0][width][.precision][type]
+#[derive(Default)]
+struct Spec {
+    fill: Option<char>,
+    align: Option<char>,
+    plus: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: Option<char>,
+}
+
+fn format_error(msg: &str, span: Span) -> Error {
+    Error::ParseError {
+        msg: format!("format: {msg}"),
+        span,
+    }
+}
+
+// the largest width or precision, the same limit Rust's `format!` has
+const MAX_WIDTH: usize = u16::MAX as usize;
+
+// the width or precision at the front of `chars`, if there is one
+fn number(chars: &mut Peekable<Chars>, what: &str, span: Span) -> Result<Option<usize>, Error> {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        digits.push(c);
+    }
+    if digits.is_empty() {
+        return Ok(None);
+    }
+    match digits.parse() {
+        Ok(n) if n <= MAX_WIDTH => Ok(Some(n)),
+        _ => Err(Error::Domain {
+            msg: format!("format: {what} {digits} is larger than {MAX_WIDTH}"),
+            span,
+        }),
+    }
+}
+
+fn parse_spec(spec: &str, span: Span) -> Result<Spec, Error> {
+    let mut out = Spec::default();
+    let is_align = |c: char| matches!(c, '<' | '>' | '^');
+    let mut chars = spec.chars().peekable();
+
+    let mut ahead = spec.chars();
+    match (ahead.next(), ahead.next()) {
+        (Some(fill), Some(align)) if is_align(align) => {
+            out.fill = Some(fill);
+            out.align = Some(align);
+            chars.nth(1);
+        }
+        (Some(align), _) if is_align(align) => {
+            out.align = Some(align);
+            chars.next();
+        }
+        _ => {}
+    }
+    out.plus = chars.next_if_eq(&'+').is_some();
+    out.alternate = chars.next_if_eq(&'#').is_some();
+    out.zero = chars.next_if_eq(&'0').is_some();
+    out.width = number(&mut chars, "width", span)?.unwrap_or(0);
+    if chars.next_if_eq(&'.').is_some() {
+        out.precision = Some(
+            number(&mut chars, "precision", span)?
+                .ok_or_else(|| format_error("precision needs digits", span))?,
+        );
+    }
+    out.kind = chars.next();
+    match (out.kind, chars.next()) {
+        (None | Some('?' | 'x' | 'X' | 'b' | 'o' | 'e'), None) => Ok(out),
+        _ => Err(format_error(&format!("unknown format spec '{spec}'"), span)),
+    }
+}
+
+// `value` laid out by `spec`
+fn render(value: &Expr, spec: &Spec, span: Span) -> Result<String, Error> {
+    let numeric = number::is_number(value);
+    let (sign, body) = match (spec.kind, value) {
+        (Some('?'), _) => (String::new(), value.to_string()),
+        (Some(kind @ ('x' | 'X' | 'b' | 'o')), _) => {
+            let Some(n) = Int::from_expr(value) else {
+                return Err(Error::IncompatibleType {
+                    op: format!("format {{:{kind}}}"),
+                    expected: "Number".to_string(),
+                    received: value.as_str(),
+                    span,
+                });
+            };
+            let n: BigInt = n.big();
+            let prefix = if spec.alternate {
+                match kind {
+                    'x' | 'X' => "0x",
+                    'b' => "0b",
+                    _ => "0o",
+                }
+            } else {
+                ""
+            };
+            let magnitude = n.magnitude();
+            let digits = match kind {
+                'x' => format!("{magnitude:x}"),
+                'X' => format!("{magnitude:X}"),
+                'b' => format!("{magnitude:b}"),
+                _ => format!("{magnitude:o}"),
+            };
+            // the prefix goes with the sign, in front of any zero padding
+            let sign = match n.sign() {
+                Sign::Minus => "-",
+                _ if spec.plus => "+",
+                _ => "",
+            };
+            (format!("{sign}{prefix}"), digits)
+        }
+        (Some('e'), _) => {
+            let Some(f) = Num::from_expr(value).map(|n| n.to_f64()) else {
+                return Err(Error::IncompatibleType {
+                    op: "format {:e}".to_string(),
+                    expected: "Number, Rational or Float".to_string(),
+                    received: value.as_str(),
+                    span,
+                });
+            };
+            match spec.precision {
+                Some(p) => (String::new(), format!("{f:.p$e}")),
+                None => (String::new(), format!("{f:e}")),
+            }
+        }
+        (_, Expr::String(s)) => match spec.precision {
+            Some(p) => (String::new(), s.chars().take(p).collect()),
+            None => (String::new(), s.clone()),
+        },
+        (_, Expr::Float(f)) => match spec.precision {
+            Some(p) => (String::new(), format!("{f:.p$}")),
+            None => (String::new(), value.to_string()),
+        },
+        (_, Expr::Char(c)) => (String::new(), c.to_string()),
+        _ => (String::new(), value.to_string()),
+    };
+
+    // split the sign off so zero padding goes between it and the digits
+    let (sign, body) = match body.strip_prefix('-') {
+        Some(rest) if numeric => ("-".to_string(), rest.to_string()),
+        _ if sign.is_empty() && numeric && spec.plus => ("+".to_string(), body),
+        _ => (sign, body),
+    };
+    let len = sign.chars().count() + body.chars().count();
+    let pad = spec.width.saturating_sub(len);
+    if spec.zero && numeric {
+        return Ok(format!("{sign}{}{body}", "0".repeat(pad)));
+    }
+    let fill = spec.fill.unwrap_or(' ').to_string();
+    let align = spec.align.unwrap_or(if numeric { '>' } else { '<' });
+    let (left, right) = match align {
+        '<' => (0, pad),
+        '^' => (pad / 2, pad - pad / 2),
+        _ => (pad, 0),
+    };
+    Ok(format!(
+        "{}{sign}{body}{}",
+        fill.repeat(left),
+        fill.repeat(right)
+    ))
+}
+
+/// `(format "x={} y={:>5.2}" x y)`, the format string with every `{...}`
+/// replaced by the next argument, or by the one its number picks. `{{` and
+/// `}}` are literal braces, `{:?}` is an argument's `repr`.
+pub fn builtin_format(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
+    let mut args = args.into_iter();
+    let template = args.next().unwrap().into_string(func, span)?;
+    let values: Vec<Expr> = args.collect();
+
+    let mut out = String::new();
+    let mut next = 0;
+    let mut used = vec![false; values.len()];
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => out.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => out.push('}'),
+            '}' => return Err(format_error("unmatched '}', write '}}' for a brace", span)),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(format_error("unclosed '{'", span)),
+                    }
+                }
+                let (index, spec) = placeholder.split_once(':').unwrap_or((&placeholder, ""));
+                let index = if index.is_empty() {
+                    next += 1;
+                    next - 1
+                } else {
+                    index
+                        .parse()
+                        .map_err(|_| format_error(&format!("bad argument index '{index}'"), span))?
+                };
+                let Some(value) = values.get(index) else {
+                    return Err(Error::WrongAmountOfArgs {
+                        func: func.to_string(),
+                        expected: index + 2,
+                        received: values.len() + 1,
+                        span,
+                    });
+                };
+                used[index] = true;
+                out.push_str(&render(value, &parse_spec(spec, span)?, span)?);
+            }
+            c => out.push(c),
+        }
+    }
+    // every argument has to be used, like the placeholders they go in
+    if used.contains(&false) {
+        return Err(Error::WrongAmountOfArgs {
+            func: func.to_string(),
+            expected: used.iter().filter(|u| **u).count() + 1,
+            received: values.len() + 1,
+            span,
+        });
+    }
+    Ok(Expr::String(out))
+}
+
+// what `str` makes of one value
+fn display(value: Expr) -> String {
+    match value {
+        Expr::String(s) => s,
+        Expr::Char(c) => c.to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// `(str x ...)`, the values printed for people and joined: strings and
+/// chars as their plain text, everything else as it prints
+pub fn builtin_str(_func: &str, args: Vec<Expr>, _span: Span) -> Result<Expr, Error> {
+    Ok(Expr::String(args.into_iter().map(display).collect()))
+}
+
+/// `(repr x)`, how `x` is written in source, strings quoted and escaped
+pub fn builtin_repr(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    Ok(Expr::String(args[0].to_string()))
+}
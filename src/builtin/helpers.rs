@@ -1,44 +1,42 @@
+use std::cmp::Ordering;
+
 use crate::ast::{Error, Expr, expect_arity};
+use crate::cells::Cells;
+use crate::number::Num;
+use crate::span::Span;
 
-pub fn builtin_range(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
+pub fn builtin_range(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
     let a = args.into_iter().next().unwrap();
     let Expr::Number(rng) = a else {
         return Err(Error::IncompatibleType {
             op: func.to_string(),
             expected: "Number".to_string(),
             received: a.as_str(),
-            line,
+            span,
         });
     };
 
     Ok(Expr::List((0..rng).map(Expr::Number).collect()))
 }
 
-pub fn builtin_sort(func: &str, mut args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
+pub fn builtin_sort(func: &str, mut args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
 
-    let mut nums: Vec<i32> = args
+    let mut nums: Vec<Num> = args
         .remove(0)
-        .into_list(func, line)?
+        .into_list(func, span)?
         .iter()
-        .map(|e| match e {
-            Expr::Number(i) => Ok(*i),
-            _ => Err(Error::IncompatibleType {
-                op: func.to_string(),
-                expected: "Number".to_string(),
-                received: e.as_str(),
-                line,
-            }),
-        })
+        .map(|e| Num::expect(e, func, span))
         .collect::<Result<Vec<_>, Error>>()?; // returns on error
-    nums.sort();
-    Ok(Expr::List(nums.iter().map(|i| Expr::Number(*i)).collect()))
+    // NaN sorts as equal to everything
+    nums.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    Ok(Expr::List(nums.into_iter().map(Num::into_expr).collect()))
 }
 
-pub fn builtin_print(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
+pub fn builtin_print(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
     let a = args.into_iter().next().unwrap();
     println!("{}", a);
-    Ok(Expr::Sexpr(Vec::new()))
+    Ok(Expr::Sexpr(Cells::new()))
 }
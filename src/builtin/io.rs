@@ -1,52 +1,64 @@
 use crate::ast::{Error, Expr, expect_arity};
 use crate::builtin::macros::single_string_op;
+use crate::cells::Cells;
 use crate::env::Env;
+use crate::span::{Source, Span};
+use lalrpop_util::ParseError;
 use std::fs;
 
-pub fn builtin_load(func: &str, e: Env, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    single_string_op!(
-        args,
-        func,
-        line,
-        |path: String, _func: &str, line: usize| {
-            let contents = fs::read_to_string(&path).map_err(|err| Error::IoError {
-                msg: format!("Failed to load file '{}': {}", path, err),
-                line,
-            })?;
+pub fn builtin_load(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    single_string_op!(args, func, span, |path: String, _func: &str, span: Span| {
+        eval_file(&path, e, span)
+    })
+}
 
-            let parser = crate::grammar::JLispParser::new();
-            let result = parser.parse(&contents).map_err(|err| Error::ParseError {
-                msg: format!("Failed to parse file '{}': {}", path, err),
-                line,
-            })?;
+/// Every form in the file at `path` evaluated in `e`, the value of the last
+pub(crate) fn eval_file(path: &str, e: &Env, span: Span) -> Result<Expr, Error> {
+    let contents = fs::read_to_string(path).map_err(|err| Error::IoError {
+        msg: format!("Failed to load file '{}': {}", path, err),
+        span,
+    })?;
+    eval_source(path, &contents, e, span)
+}
 
-            let mut last = Expr::Sexpr(vec![]);
-            for expr in result.exprs {
-                last = expr.eval(e, line)?;
-            }
+/// Every form in `contents` evaluated in `e`, spans in it name the file `name`
+pub(crate) fn eval_source(name: &str, contents: &str, e: &Env, span: Span) -> Result<Expr, Error> {
+    let source = Source::new(name, contents);
+    let parser = crate::grammar::JLispParser::new();
+    let result = parser
+        .parse(&source, contents)
+        .map_err(|err| Error::ParseError {
+            msg: format!("Failed to parse file '{}': {}", name, err),
+            span: parse_error_span(&source, &err).unwrap_or(span),
+        })?;
 
-            Ok(last)
-        }
-    )
+    let mut last = Expr::Sexpr(Cells::new());
+    for expr in result.exprs {
+        last = expr.eval(e, Span::file(source.file()))?;
+    }
+
+    Ok(last)
 }
 
-pub fn builtin_read(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    single_string_op!(
-        args,
-        func,
-        line,
-        |path: String, _func: &str, line: usize| {
-            let contents = fs::read_to_string(&path).map_err(|err| Error::IoError {
-                msg: format!("Failed to load file '{}': {}", path, err),
-                line,
-            })?;
-            Ok(Expr::String(contents))
+// where in the loaded file the parser gave up
+fn parse_error_span<T, E>(source: &Source, err: &ParseError<usize, T, E>) -> Option<Span> {
+    match err {
+        ParseError::InvalidToken { location } | ParseError::UnrecognizedEof { location, .. } => {
+            Some(source.span(*location, *location))
+        }
+        ParseError::UnrecognizedToken { token, .. } | ParseError::ExtraToken { token } => {
+            Some(source.span(token.0, token.2))
         }
-    )
+        ParseError::User { .. } => None,
+    }
 }
 
-pub fn builtin_error(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
-    let msg = args[0].clone().into_string(func, line)?;
-    Err(Error::ParseError { msg, line })
+pub fn builtin_read(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    single_string_op!(args, func, span, |path: String, _func: &str, span: Span| {
+        let contents = fs::read_to_string(&path).map_err(|err| Error::IoError {
+            msg: format!("Failed to load file '{}': {}", path, err),
+            span,
+        })?;
+        Ok(Expr::String(contents))
+    })
 }
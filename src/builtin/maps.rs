@@ -0,0 +1,95 @@
+use crate::ast::{Error, Expr, Map, call, expect_arity, expect_nonempty};
+use crate::env::Env;
+use crate::span::Span;
+use std::sync::Arc;
+
+// the map itself if nothing else holds on to it, a copy otherwise
+fn take_map(expr: Expr, func: &str, span: Span) -> Result<Map, Error> {
+    let map = expr.into_map(func, span)?;
+    Ok(Arc::try_unwrap(map).unwrap_or_else(|map| (*map).clone()))
+}
+
+/// `(get m k)` or `(get m k default)`, missing keys give the default or nil
+pub fn builtin_get(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    if !(2..=3).contains(&args.len()) {
+        expect_arity(func, &args, 2, span)?;
+    }
+    let mut args = args.into_iter();
+    let map = args.next().unwrap().into_map(func, span)?;
+    let key = args.next().unwrap();
+    let default = args.next().unwrap_or(Expr::Nil);
+    Ok(map.get(&key).cloned().unwrap_or(default))
+}
+
+/// `(assoc m k v ...)`, a map with every `k` bound to its `v`
+pub fn builtin_assoc(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
+    if args.len().is_multiple_of(2) {
+        // a key without a value
+        expect_arity(func, &args, args.len() + 1, span)?;
+    }
+    let mut args = args.into_iter();
+    let mut map = take_map(args.next().unwrap(), func, span)?;
+    while let (Some(k), Some(v)) = (args.next(), args.next()) {
+        map.insert(k, v);
+    }
+    Ok(Expr::Map(Arc::new(map)))
+}
+
+/// `(dissoc m k ...)`, a map without the keys `k`
+pub fn builtin_dissoc(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
+    let mut args = args.into_iter();
+    let mut map = take_map(args.next().unwrap(), func, span)?;
+    for k in args {
+        map.shift_remove(&k);
+    }
+    Ok(Expr::Map(Arc::new(map)))
+}
+
+pub fn builtin_keys(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let map = args.into_iter().next().unwrap().into_map(func, span)?;
+    Ok(Expr::List(map.keys().cloned().collect()))
+}
+
+pub fn builtin_vals(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let map = args.into_iter().next().unwrap().into_map(func, span)?;
+    Ok(Expr::List(map.values().cloned().collect()))
+}
+
+pub fn builtin_has(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    let map = args[0].clone().into_map(func, span)?;
+    Ok(Expr::Bool(map.contains_key(&args[1])))
+}
+
+/// `(update m k f)`, binds `k` to `f` applied to its value, or to nil if
+/// `k` is missing
+pub fn builtin_update(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 3, span)?;
+    let mut args = args.into_iter();
+    let mut map = take_map(args.next().unwrap(), func, span)?;
+    let key = args.next().unwrap();
+    let f = args.next().unwrap();
+
+    let old = map.get(&key).cloned().unwrap_or(Expr::Nil);
+    let new = call(e, f, vec![old], span)?;
+    map.insert(key, new);
+    Ok(Expr::Map(Arc::new(map)))
+}
+
+/// `(merge m ...)`, keys of later maps win
+pub fn builtin_merge(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let mut args = args.into_iter();
+    let Some(first) = args.next() else {
+        return Ok(Expr::Map(Arc::new(Map::new())));
+    };
+    let mut map = take_map(first, func, span)?;
+    for other in args {
+        let other = other.into_map(func, span)?;
+        map.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    Ok(Expr::Map(Arc::new(map)))
+}
@@ -1,24 +1,33 @@
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+
 use crate::ast::{Error, Expr, expect_arity};
+use crate::number::{Int, Num};
+use crate::span::Span;
 
 /// Helper function to create incompatible type errors
-fn incompatible_type_error(func: &str, expected: &str, received: String, line: usize) -> Result<Expr, Error> {
+fn incompatible_type_error(func: &str, expected: &str, received: String, span: Span) -> Result<Expr, Error> {
     Err(Error::IncompatibleType {
         op: func.to_string(),
         expected: expected.to_string(),
         received,
-        line,
+        span,
     })
 }
 
+/// A number of any kind as a float
+fn float_arg(e: &Expr) -> Option<f64> {
+    Num::from_expr(e).map(|n| n.to_f64())
+}
+
 /// Macro for unary math operations that return Float
 macro_rules! unary_math_op {
     ($func_name:ident, $rust_op:ident) => {
-        pub fn $func_name(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-            expect_arity(func, &args, 1, line)?;
-            match args[0] {
-                Expr::Number(n) => Ok(Expr::Float((n as f32).$rust_op())),
-                Expr::Float(f) => Ok(Expr::Float(f.$rust_op())),
-                _ => incompatible_type_error(func, "Number or Float", args[0].as_str(), line),
+        pub fn $func_name(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+            expect_arity(func, &args, 1, span)?;
+            match float_arg(&args[0]) {
+                Some(f) => Ok(Expr::Float(f.$rust_op())),
+                None => incompatible_type_error(func, "Number or Float", args[0].as_str(), span),
             }
         }
     };
@@ -27,30 +36,20 @@ macro_rules! unary_math_op {
 /// Macro for unary math operations with validation
 macro_rules! unary_math_op_with_validation {
     ($func_name:ident, $rust_op:ident, $validation:expr, $error_msg:expr) => {
-        pub fn $func_name(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-            expect_arity(func, &args, 1, line)?;
-            match args[0] {
-                Expr::Number(n) => {
-                    if !$validation(n as f32) {
-                        return Err(Error::ParseError {
-                            msg: $error_msg.to_string(),
-                            line,
-                        });
-                    }
-                    let result = (n as f32).$rust_op();
-                    Ok(Expr::Float(result))
-                }
-                Expr::Float(f) => {
+        pub fn $func_name(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+            expect_arity(func, &args, 1, span)?;
+            match float_arg(&args[0]) {
+                Some(f) => {
                     if !$validation(f) {
-                        return Err(Error::ParseError {
+                        return Err(Error::Domain {
                             msg: $error_msg.to_string(),
-                            line,
+                            span,
                         });
                     }
                     let result = f.$rust_op();
                     Ok(Expr::Float(result))
                 }
-                _ => incompatible_type_error(func, "Number or Float", args[0].as_str(), line),
+                None => incompatible_type_error(func, "Number or Float", args[0].as_str(), span),
             }
         }
     };
@@ -59,52 +58,72 @@ macro_rules! unary_math_op_with_validation {
 /// Macro for binary math operations
 macro_rules! binary_math_op {
     ($func_name:ident, $rust_op:ident) => {
-        pub fn $func_name(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-            expect_arity(func, &args, 2, line)?;
-            match (&args[0], &args[1]) {
-                (Expr::Number(l), Expr::Number(r)) => Ok(Expr::Number((*l).$rust_op(*r))),
-                (Expr::Float(l), Expr::Float(r)) => Ok(Expr::Float((*l).$rust_op(*r))),
-                (Expr::Number(l), Expr::Float(r)) => Ok(Expr::Float((*l as f32).$rust_op(*r))),
-                (Expr::Float(l), Expr::Number(r)) => Ok(Expr::Float(l.$rust_op(*r as f32))),
-                _ => incompatible_type_error(func, "Number or Float", args[0].as_str(), line),
+        pub fn $func_name(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+            expect_arity(func, &args, 2, span)?;
+            match (Num::from_expr(&args[0]), Num::from_expr(&args[1])) {
+                (Some(l), Some(r)) => Ok(l.$rust_op(r).into_expr()),
+                (None, _) => {
+                    incompatible_type_error(func, "Number or Float", args[0].as_str(), span)
+                }
+                (_, None) => {
+                    incompatible_type_error(func, "Number or Float", args[1].as_str(), span)
+                }
             }
         }
     };
 }
 
-/// Helper function to truncate float to int if it's close enough to an integer
-fn truncate_float(f: f32) -> Expr {
-    Expr::Number(f.floor() as i32)
+/// Helper function to truncate float to int, dropping the fraction towards zero
+fn truncate_float(f: f64) -> Expr {
+    match BigInt::from_f64(f.trunc()) {
+        Some(n) => Int::Big(n).into_expr(),
+        None => Expr::Float(f),
+    }
 }
 
-pub fn builtin_truncate(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
+pub fn builtin_truncate(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
     match args[0] {
-        Expr::Number(n) => Ok(Expr::Number(n)),
+        Expr::Number(_) | Expr::BigInt(_) => Ok(args[0].clone()),
+        Expr::Rational(ref r) => Ok(Int::Big(r.trunc().to_integer()).into_expr()),
         Expr::Float(f) => Ok(truncate_float(f)),
-        _ => incompatible_type_error(func, "Number or Float", args[0].as_str(), line),
+        _ => incompatible_type_error(func, "Number or Float", args[0].as_str(), span),
     }
 }
 
 // Generate sqrt with validation for non-negative arguments
 unary_math_op_with_validation!(builtin_sqrt, sqrt, |x| x >= 0.0, "sqrt argument must be non-negative");
 
-pub fn builtin_abs(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
-    match args[0] {
-        Expr::Number(n) => Ok(Expr::Number(n.abs())),
-        Expr::Float(f) => Ok(Expr::Float(f.abs())),
-        _ => incompatible_type_error(func, "Number or Float", args[0].as_str(), line),
+pub fn builtin_abs(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    match Num::from_expr(&args[0]) {
+        Some(n) => Ok(n.abs().into_expr()),
+        None => incompatible_type_error(func, "Number or Float", args[0].as_str(), span),
     }
 }
 // Generate binary operations
 binary_math_op!(builtin_min, min);
 binary_math_op!(builtin_max, max);
 
+/// Macro for rounding operations, exact for integers and rationals
+macro_rules! rounding_op {
+    ($func_name:ident, $rust_op:ident) => {
+        pub fn $func_name(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+            expect_arity(func, &args, 1, span)?;
+            match Num::from_expr(&args[0]) {
+                Some(Num::Int(n)) => Ok(n.into_expr()),
+                Some(Num::Ratio(r)) => Ok(Int::Big(r.$rust_op().to_integer()).into_expr()),
+                Some(Num::Float(f)) => Ok(Expr::Float(f.$rust_op())),
+                None => incompatible_type_error(func, "Number or Float", args[0].as_str(), span),
+            }
+        }
+    };
+}
+
 // Generate rounding operations
-unary_math_op!(builtin_floor, floor);
-unary_math_op!(builtin_ceil, ceil);
-unary_math_op!(builtin_round, round);
+rounding_op!(builtin_floor, floor);
+rounding_op!(builtin_ceil, ceil);
+rounding_op!(builtin_round, round);
 
 // Generate trigonometric operations
 unary_math_op!(builtin_sin, sin);
@@ -114,3 +133,49 @@ unary_math_op!(builtin_tan, tan);
 // Generate logarithmic and exponential operations
 unary_math_op_with_validation!(builtin_log, ln, |x| x > 0.0, "log argument must be positive");
 unary_math_op!(builtin_exp, exp);
+
+/// `(format-float x digits)`, `x` printed with exactly `digits` decimals
+pub fn builtin_format_float(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    let Some(x) = float_arg(&args[0]) else {
+        return incompatible_type_error(func, "Number or Float", args[0].as_str(), span);
+    };
+    let digits = args[1].clone().into_number(func, span)?;
+    let Ok(digits) = usize::try_from(digits) else {
+        return Err(Error::Domain {
+            msg: "format-float digits must be non-negative".to_string(),
+            span,
+        });
+    };
+    Ok(Expr::String(format!("{x:.digits$}")))
+}
+
+/// `numerator` and `denominator` of a fraction in lowest terms, integers are
+/// over 1
+pub fn builtin_fraction_part(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let (numer, denom) = match &args[0] {
+        Expr::Rational(r) => (r.numer().clone(), r.denom().clone()),
+        e => match Int::from_expr(e) {
+            Some(n) => {
+                return Ok(if func == "numerator" {
+                    n.into_expr()
+                } else {
+                    Expr::Number(1)
+                });
+            }
+            None => return incompatible_type_error(func, "Number or Rational", e.as_str(), span),
+        },
+    };
+    let part = if func == "numerator" { numer } else { denom };
+    Ok(Int::Big(part).into_expr())
+}
+
+/// `(exact->inexact x)`, the float closest to a number of any kind
+pub fn builtin_exact_to_inexact(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    match float_arg(&args[0]) {
+        Some(f) => Ok(Expr::Float(f)),
+        None => incompatible_type_error(func, "Number, Rational or Float", args[0].as_str(), span),
+    }
+}
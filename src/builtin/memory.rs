@@ -0,0 +1,22 @@
+use crate::ast::{Error, Expr, expect_arity};
+use crate::env::Env;
+use crate::gc;
+use crate::span::Span;
+
+pub fn builtin_gc(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 0, span)?;
+    Ok(Expr::Number(gc::collect(e) as i64))
+}
+
+/// `[live freed]` environment counts
+pub fn builtin_gc_stats(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 0, span)?;
+    let stats = gc::stats(e);
+    Ok(Expr::List(
+        vec![
+            Expr::Number(stats.live as i64),
+            Expr::Number(stats.freed as i64),
+        ]
+        .into(),
+    ))
+}
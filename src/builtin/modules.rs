@@ -0,0 +1,177 @@
+use crate::ast::{Error, Expr, Map, expect_nonempty};
+use crate::builtin::io::{eval_file, eval_source};
+use crate::builtin::setup_module;
+use crate::cells::Cells;
+use crate::env::Env;
+use crate::span::Span;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// the modules that ship with the interpreter, built into it so it finds them
+// wherever it is installed
+const STDLIB: &[(&str, &str)] = &[("str.jl", include_str!("../../lib/str.jl"))];
+
+// a module `resolve` found, a file or the source of a stdlib one
+struct Module {
+    path: PathBuf,
+    source: Option<&'static str>,
+}
+
+// where module files are looked for: next to the importing file, then in
+// the directories of `JLISP_PATH`
+fn search_path(span: Span) -> Vec<PathBuf> {
+    let file = span.file.name();
+    let mut dirs = vec![
+        Path::new(&file)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+    ];
+    if let Some(paths) = env::var_os("JLISP_PATH") {
+        dirs.extend(env::split_paths(&paths));
+    }
+    dirs
+}
+
+// the module `name`, a file on the search path or else one of the stdlib
+fn resolve(name: &str, span: Span) -> Result<Module, Error> {
+    let file = if name.ends_with(".jl") {
+        name.to_string()
+    } else {
+        format!("{name}.jl")
+    };
+    let dirs = search_path(span);
+    let Some(path) = dirs.iter().map(|dir| dir.join(&file)).find(|p| p.is_file()) else {
+        if let Some((file, source)) = STDLIB.iter().find(|(f, _)| *f == file) {
+            return Ok(Module {
+                path: PathBuf::from(format!("<stdlib>/{file}")),
+                source: Some(source),
+            });
+        }
+        let searched: Vec<String> = dirs.iter().map(|d| format!("'{}'", d.display())).collect();
+        return Err(Error::IoError {
+            msg: format!(
+                "no module '{name}' in {} or the stdlib",
+                searched.join(", ")
+            ),
+            span,
+        });
+    };
+    let path = path.canonicalize().map_err(|err| Error::IoError {
+        msg: format!("Failed to load file '{}': {}", path.display(), err),
+        span,
+    })?;
+    Ok(Module { path, source: None })
+}
+
+// the bindings `module` defines, evaluated on first import and cached by
+// the interpreter `e` belongs to after that
+fn load_module(module: Module, e: &Env, span: Span) -> Result<Arc<Map>, Error> {
+    let path = module.path;
+    let state = e.state();
+    let cached = state.modules.lock().unwrap().get(&path).cloned();
+    if let Some(ns) = cached {
+        return Ok(ns);
+    }
+    let cycle = {
+        let importing = state.importing.lock().unwrap();
+        importing.iter().position(|p| *p == path).map(|start| {
+            let chain: Vec<String> = importing[start..]
+                .iter()
+                .chain([&path])
+                .map(|p| p.display().to_string())
+                .collect();
+            chain.join(" -> ")
+        })
+    };
+    if let Some(cycle) = cycle {
+        return Err(Error::Import {
+            msg: format!("circular import {cycle}"),
+            span,
+        });
+    }
+
+    // a module gets a root env of its own, so its `def`s stay in it
+    let env = setup_module(e);
+    let before = env.with_bindings(HashMap::clone);
+    state.importing.lock().unwrap().push(path.clone());
+    let name = path.to_string_lossy();
+    let result = match module.source {
+        Some(source) => eval_source(&name, source, &env, span),
+        None => eval_file(&name, &env, span),
+    };
+    state.importing.lock().unwrap().pop();
+    result?;
+
+    let mut defined: Vec<(String, Expr)> = env
+        .with_bindings(HashMap::clone)
+        .into_iter()
+        .filter(|(name, val)| before.get(name) != Some(val))
+        .collect();
+    defined.sort_by(|a, b| a.0.cmp(&b.0));
+    let ns: Arc<Map> = Arc::new(
+        defined
+            .into_iter()
+            .map(|(name, val)| (Expr::String(name), val))
+            .collect(),
+    );
+    state.modules.lock().unwrap().insert(path, ns.clone());
+    Ok(ns)
+}
+
+/// `(import name)`, `(import name as alias)`, `(import name [a b])` and
+/// `(import name [a b] as alias)`. The module `name.jl` is looked for next
+/// to the importing file, in `JLISP_PATH` and in the stdlib, and evaluated
+/// once into its own namespace. Its definitions are then `name/a` (or
+/// `alias/a`), and the ones listed are also bound without the prefix.
+pub fn builtin_import(func: &str, e: &Env, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
+    let name = match &args[0] {
+        Expr::Symbol(name) | Expr::String(name) => name.clone(),
+        other => {
+            return Err(Error::IncompatibleType {
+                op: func.to_string(),
+                expected: "Symbol or String".to_string(),
+                received: other.as_str(),
+                span,
+            });
+        }
+    };
+    let (names, alias) = match &args[1..] {
+        [] => (None, None),
+        [Expr::List(names)] => (Some(names), None),
+        [Expr::Symbol(kw), Expr::Symbol(alias)] if kw == "as" => (None, Some(alias.clone())),
+        [Expr::List(names), Expr::Symbol(kw), Expr::Symbol(alias)] if kw == "as" => {
+            (Some(names), Some(alias.clone()))
+        }
+        _ => {
+            return Err(Error::ParseError {
+                msg: "import: expected (import name [names...] as alias)".to_string(),
+                span,
+            });
+        }
+    };
+
+    let ns = load_module(resolve(&name, span)?, e, span)?;
+    let prefix = alias.unwrap_or_else(|| {
+        let stem = Path::new(&name).file_stem().unwrap_or_default();
+        stem.to_string_lossy().into_owned()
+    });
+    if let Some(names) = names {
+        for n in names.iter() {
+            let n = n.clone().into_symbol(func, span)?;
+            let Some(val) = ns.get(&Expr::String(n.clone())) else {
+                return Err(Error::UndefinedSymbol {
+                    sym: format!("{prefix}/{n}"),
+                    span,
+                });
+            };
+            e.insert(n, val.clone());
+        }
+    }
+    // bound as `prefix/` so the namespace doesn't shadow a plain `prefix`
+    e.insert(format!("{prefix}/"), Expr::Map(ns));
+    Ok(Expr::Sexpr(Cells::new()))
+}
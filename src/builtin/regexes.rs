@@ -0,0 +1,112 @@
+use crate::ast::{Error, Expr, Map, expect_arity};
+use crate::span::Span;
+use regex::{Captures, Regex};
+use std::sync::Arc;
+
+// a compiled regex, or a pattern string compiled on the spot
+fn to_regex(expr: Expr, func: &str, span: Span) -> Result<Arc<Regex>, Error> {
+    match expr {
+        Expr::Regex(re) => Ok(re),
+        Expr::String(pattern) => compile(&pattern, span).map(Arc::new),
+        _ => Err(Error::IncompatibleType {
+            op: func.to_string(),
+            expected: "Regex or String".to_string(),
+            received: expr.as_str(),
+            span,
+        }),
+    }
+}
+
+fn compile(pattern: &str, span: Span) -> Result<Regex, Error> {
+    Regex::new(pattern).map_err(|err| Error::ParseError {
+        msg: format!("invalid regex: {err}"),
+        span,
+    })
+}
+
+// the regex and the string every `re-` builtin starts with
+fn regex_args(
+    func: &str,
+    args: Vec<Expr>,
+    n: usize,
+    span: Span,
+) -> Result<(Arc<Regex>, String, Vec<Expr>), Error> {
+    expect_arity(func, &args, n, span)?;
+    let mut args = args.into_iter();
+    let re = to_regex(args.next().unwrap(), func, span)?;
+    let s = args.next().unwrap().into_string(func, span)?;
+    Ok((re, s, args.collect()))
+}
+
+fn string(s: &str) -> Expr {
+    Expr::String(s.to_string())
+}
+
+/// `(regex pattern)`, compiled once to use with every `re-` builtin
+pub fn builtin_regex(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let pattern = args.into_iter().next().unwrap().into_string(func, span)?;
+    Ok(Expr::Regex(Arc::new(compile(&pattern, span)?)))
+}
+
+/// `(re-match re s)`, the first part of `s` that matches or nil
+pub fn builtin_re_match(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let (re, s, _) = regex_args(func, args, 2, span)?;
+    Ok(re.find(&s).map_or(Expr::Nil, |m| string(m.as_str())))
+}
+
+/// `(re-find-all re s)`, every non-overlapping match in `s`
+pub fn builtin_re_find_all(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let (re, s, _) = regex_args(func, args, 2, span)?;
+    Ok(Expr::List(
+        re.find_iter(&s).map(|m| string(m.as_str())).collect(),
+    ))
+}
+
+// the groups of a match: a map from name to text if the regex names any of
+// them, `[whole group1 group2 ...]` otherwise. Groups that took no part in
+// the match are nil.
+fn groups(re: &Regex, caps: &Captures) -> Expr {
+    let group = |i: usize| caps.get(i).map_or(Expr::Nil, |m| string(m.as_str()));
+    if re.capture_names().any(|name| name.is_some()) {
+        let map: Map = re
+            .capture_names()
+            .enumerate()
+            .filter_map(|(i, name)| Some((string(name?), group(i))))
+            .collect();
+        Expr::Map(Arc::new(map))
+    } else {
+        Expr::List((0..caps.len()).map(group).collect())
+    }
+}
+
+/// `(re-captures re s)`, the groups of the first match or nil, or with
+/// `(re-captures re s true)` a list of the groups of every match
+pub fn builtin_re_captures(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let n = if args.len() == 3 { 3 } else { 2 };
+    let (re, s, rest) = regex_args(func, args, n, span)?;
+    if rest.first().is_some_and(Expr::is_truthy) {
+        return Ok(Expr::List(
+            re.captures_iter(&s)
+                .map(|caps| groups(&re, &caps))
+                .collect(),
+        ));
+    }
+    Ok(re.captures(&s).map_or(Expr::Nil, |caps| groups(&re, &caps)))
+}
+
+/// `(re-replace re s replacement)`, every match replaced. `$1` and `$name`
+/// in the replacement stand for groups of the match.
+pub fn builtin_re_replace(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let (re, s, rest) = regex_args(func, args, 3, span)?;
+    let replacement = rest.into_iter().next().unwrap().into_string(func, span)?;
+    Ok(Expr::String(
+        re.replace_all(&s, replacement.as_str()).into_owned(),
+    ))
+}
+
+/// `(re-split re s)`, the parts of `s` between matches
+pub fn builtin_re_split(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    let (re, s, _) = regex_args(func, args, 2, span)?;
+    Ok(Expr::List(re.split(&s).map(string).collect()))
+}
@@ -0,0 +1,72 @@
+// the interior mutability clippy sees in `Expr` is in envs and cached code,
+// neither of which is hashed
+#![allow(clippy::mutable_key_type)]
+
+use crate::ast::{Error, Expr, Set, expect_arity, expect_nonempty};
+use crate::span::Span;
+use std::sync::Arc;
+
+// the set itself if nothing else holds on to it, a copy otherwise
+fn take_set(expr: Expr, func: &str, span: Span) -> Result<Set, Error> {
+    let set = expr.into_set(func, span)?;
+    Ok(Arc::try_unwrap(set).unwrap_or_else(|set| (*set).clone()))
+}
+
+/// `(set l)`, the distinct elements of a list
+pub fn builtin_set(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let items = args.into_iter().next().unwrap().into_list(func, span)?;
+    Ok(Expr::Set(Arc::new(items.into_iter().collect())))
+}
+
+/// `(set-add s x ...)`
+pub fn builtin_set_add(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
+    let mut args = args.into_iter();
+    let mut set = take_set(args.next().unwrap(), func, span)?;
+    set.extend(args);
+    Ok(Expr::Set(Arc::new(set)))
+}
+
+/// `(set-remove s x ...)`
+pub fn builtin_set_remove(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
+    let mut args = args.into_iter();
+    let mut set = take_set(args.next().unwrap(), func, span)?;
+    for x in args {
+        set.remove(&x);
+    }
+    Ok(Expr::Set(Arc::new(set)))
+}
+
+/// `(member? s x)`
+pub fn builtin_member(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    let set = args[0].clone().into_set(func, span)?;
+    Ok(Expr::Bool(set.contains(&args[1])))
+}
+
+/// `union`, `intersection` and `difference` of the first set with the rest
+pub fn builtin_set_op(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_nonempty(func, &args, span)?;
+    let mut args = args.into_iter();
+    let mut set = take_set(args.next().unwrap(), func, span)?;
+    for other in args {
+        let other = other.into_set(func, span)?;
+        match func {
+            "union" => set.extend(other.iter().cloned()),
+            "intersection" => set.retain(|x| other.contains(x)),
+            "difference" => set.retain(|x| !other.contains(x)),
+            _ => panic!(),
+        }
+    }
+    Ok(Expr::Set(Arc::new(set)))
+}
+
+/// `(subset? a b)`, whether every element of `a` is in `b`
+pub fn builtin_subset(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    let a = args[0].clone().into_set(func, span)?;
+    let b = args[1].clone().into_set(func, span)?;
+    Ok(Expr::Bool(a.is_subset(&b)))
+}
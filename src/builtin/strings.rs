@@ -1,39 +1,38 @@
 use crate::ast::{Error, Expr, expect_arity};
+use crate::number;
+use crate::span::Span;
 
-pub fn builtin_chars(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
-    let s = args[0].clone().into_string(func, line)?;
+pub fn builtin_chars(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let s = args[0].clone().into_string(func, span)?;
     Ok(Expr::List(s.chars().map(Expr::Char).collect()))
 }
 
-pub fn builtin_int(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 1, line)?;
-    match args[0].clone().into_string(func, line)?.parse::<i32>() {
-        Ok(v) => Ok(Expr::Number(v)),
-        Err(_) => Err(Error::ParseError {
+pub fn builtin_int(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    match number::parse_int(args[0].clone().into_string(func, span)?.trim()) {
+        Some(v) => Ok(v),
+        None => Err(Error::Domain {
             msg: "couldn't parse int from string".to_string(),
-            line,
+            span,
         }),
     }
 }
 
-pub fn builtin_str_sub(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr, Error> {
-    expect_arity(func, &args, 3, line)?;
-    let s = args[0].clone().into_string(func, line)?;
-    let start = args[1].clone().into_number(func, line)?;
-    let end = args[2].clone().into_number(func, line)?;
-
+/// `(str-sub s start end)`, the chars of `s` from `start` up to `end`
+#[jlisp::builtin(name = "str-sub")]
+fn str_sub(s: String, start: i64, end: i64, span: Span) -> Result<String, Error> {
     if start < 0 || end < 0 {
-        return Err(Error::ParseError {
+        return Err(Error::Domain {
             msg: "substring indices must be non-negative".to_string(),
-            line,
+            span,
         });
     }
 
     if start > end {
-        return Err(Error::ParseError {
+        return Err(Error::Domain {
             msg: "substring start index must be <= end index".to_string(),
-            line,
+            span,
         });
     }
 
@@ -42,12 +41,223 @@ pub fn builtin_str_sub(func: &str, args: Vec<Expr>, line: usize) -> Result<Expr,
     let end_usize = end as usize;
 
     if end_usize > chars.len() {
-        return Err(Error::ParseError {
+        return Err(Error::Domain {
             msg: "substring end index out of bounds".to_string(),
-            line,
+            span,
         });
     }
 
-    let substring: String = chars[start_usize..end_usize].iter().collect();
-    Ok(Expr::String(substring))
+    Ok(chars[start_usize..end_usize].iter().collect())
+}
+
+// a String arg, or a Char standing for the one-char string
+fn text_arg(arg: &Expr, func: &str, span: Span) -> Result<String, Error> {
+    match arg {
+        Expr::String(s) => Ok(s.clone()),
+        Expr::Char(c) => Ok(c.to_string()),
+        _ => Err(Error::IncompatibleType {
+            op: func.to_string(),
+            expected: "String or Char".to_string(),
+            received: arg.as_str(),
+            span,
+        }),
+    }
+}
+
+/// `upper` and `lower`, of a String or a Char
+pub fn builtin_case(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let upper = func == "upper";
+    match &args[0] {
+        // a few chars change length with their case, those stay as they are
+        Expr::Char(c) => {
+            let changed: Vec<char> = if upper {
+                c.to_uppercase().collect()
+            } else {
+                c.to_lowercase().collect()
+            };
+            Ok(Expr::Char(match changed[..] {
+                [changed] => changed,
+                _ => *c,
+            }))
+        }
+        arg => {
+            let s = text_arg(arg, func, span)?;
+            Ok(Expr::String(if upper {
+                s.to_uppercase()
+            } else {
+                s.to_lowercase()
+            }))
+        }
+    }
+}
+
+/// `trim`, `trim-left` and `trim-right`, whitespace off one or both ends
+pub fn builtin_trim(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let s = args[0].clone().into_string(func, span)?;
+    Ok(Expr::String(
+        match func {
+            "trim-left" => s.trim_start(),
+            "trim-right" => s.trim_end(),
+            _ => s.trim(),
+        }
+        .to_string(),
+    ))
+}
+
+/// `(starts-with? s prefix)` and `(ends-with? s suffix)`
+pub fn builtin_affix(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    let s = args[0].clone().into_string(func, span)?;
+    let affix = text_arg(&args[1], func, span)?;
+    Ok(Expr::Bool(match func {
+        "starts-with?" => s.starts_with(&affix),
+        _ => s.ends_with(&affix),
+    }))
+}
+
+/// `(index-of s sub)`, the char index `sub` first appears at or nil
+pub fn builtin_index_of(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    let s = args[0].clone().into_string(func, span)?;
+    let sub = text_arg(&args[1], func, span)?;
+    Ok(match s.find(&sub) {
+        Some(byte) => Expr::Number(s[..byte].chars().count() as i64),
+        None => Expr::Nil,
+    })
+}
+
+/// `(replace s from to)`, every `from` in `s` replaced by `to`
+pub fn builtin_replace(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 3, span)?;
+    let s = args[0].clone().into_string(func, span)?;
+    let from = text_arg(&args[1], func, span)?;
+    let to = text_arg(&args[2], func, span)?;
+    if from.is_empty() {
+        return Err(Error::Domain {
+            msg: "replace needs something to replace".to_string(),
+            span,
+        });
+    }
+    Ok(Expr::String(s.replace(&from, &to)))
+}
+
+/// `(repeat s n)`, `s` written `n` times over
+pub fn builtin_repeat(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 2, span)?;
+    let s = text_arg(&args[0], func, span)?;
+    let n = args[1].clone().into_number(func, span)?;
+    let Ok(n) = usize::try_from(n) else {
+        return Err(Error::Domain {
+            msg: "repeat count must be non-negative".to_string(),
+            span,
+        });
+    };
+    Ok(Expr::String(s.repeat(n)))
+}
+
+/// `reverse` of a String or a List
+pub fn builtin_reverse(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    match args.into_iter().next().unwrap() {
+        Expr::String(s) => Ok(Expr::String(s.chars().rev().collect())),
+        Expr::List(l) => Ok(Expr::List(l.iter().rev().cloned().collect())),
+        arg => Err(Error::IncompatibleType {
+            op: func.to_string(),
+            expected: "String or List".to_string(),
+            received: arg.as_str(),
+            span,
+        }),
+    }
+}
+
+/// `(pad-left s width)` and `(pad-right s width)`, `s` padded with spaces,
+/// or with the Char given after `width`, to at least `width` chars
+pub fn builtin_pad(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    if args.len() != 3 {
+        expect_arity(func, &args, 2, span)?;
+    }
+    let s = text_arg(&args[0], func, span)?;
+    let width = args[1].clone().into_number(func, span)?;
+    let fill = match args.get(2) {
+        Some(Expr::Char(c)) => *c,
+        Some(arg) => {
+            return Err(Error::IncompatibleType {
+                op: func.to_string(),
+                expected: "Char".to_string(),
+                received: arg.as_str(),
+                span,
+            });
+        }
+        None => ' ',
+    };
+    let pad = usize::try_from(width)
+        .unwrap_or(0)
+        .saturating_sub(s.chars().count());
+    let padding: String = std::iter::repeat_n(fill, pad).collect();
+    Ok(Expr::String(match func {
+        "pad-left" => padding + &s,
+        _ => s + &padding,
+    }))
+}
+
+/// `lines` and `words` of a String, split on line breaks and on runs of
+/// whitespace
+pub fn builtin_lines_words(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let s = args[0].clone().into_string(func, span)?;
+    let parts: Vec<&str> = match func {
+        "lines" => s.lines().collect(),
+        _ => s.split_whitespace().collect(),
+    };
+    Ok(Expr::List(
+        parts
+            .into_iter()
+            .map(|p| Expr::String(p.to_string()))
+            .collect(),
+    ))
+}
+
+/// `(list->string l)`, the Chars and Strings in `l` joined, the inverse of
+/// `chars`
+pub fn builtin_list_to_string(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let list = args[0].clone().into_list(func, span)?;
+    let mut out = String::new();
+    for item in list.iter() {
+        out.push_str(&text_arg(item, func, span)?);
+    }
+    Ok(Expr::String(out))
+}
+
+/// `digit?`, `alpha?` and `whitespace?` of a Char, or of a non-empty String
+/// that is made of nothing else
+pub fn builtin_char_class(func: &str, args: Vec<Expr>, span: Span) -> Result<Expr, Error> {
+    expect_arity(func, &args, 1, span)?;
+    let test: fn(char) -> bool = match func {
+        "digit?" => |c| c.is_ascii_digit(),
+        "alpha?" => char::is_alphabetic,
+        _ => char::is_whitespace,
+    };
+    let s = text_arg(&args[0], func, span)?;
+    Ok(Expr::Bool(!s.is_empty() && s.chars().all(test)))
+}
+
+/// `(char->int c)`, the code point of `c`
+#[jlisp::builtin(name = "char->int")]
+fn char_to_int(c: char) -> u32 {
+    c as u32
+}
+
+/// `(int->char n)`, the Char with code point `n`
+#[jlisp::builtin(name = "int->char")]
+fn int_to_char(n: i64, span: Span) -> Result<char, Error> {
+    match u32::try_from(n).ok().and_then(char::from_u32) {
+        Some(c) => Ok(c),
+        None => Err(Error::Domain {
+            msg: format!("{n} is not a valid char"),
+            span,
+        }),
+    }
 }
@@ -0,0 +1,172 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
+
+use crate::ast::Expr;
+use crate::span::Span;
+use crate::vm::Chunk;
+
+struct Storage {
+    items: Vec<Expr>,
+    // where the parser read these cells from
+    span: Option<Span>,
+    // bytecode for evaluating these cells as a call, compiled on first use
+    code: OnceLock<Arc<Chunk>>,
+}
+
+/// Shared, immutable backing store for `Sexpr` and `List` values.
+///
+/// Cloning is O(1) and so is taking the tail, so binding a big list to a
+/// symbol or recursing over it with `tail` doesn't copy the elements.
+#[derive(Clone)]
+pub struct Cells {
+    items: Arc<Storage>,
+    start: usize,
+}
+
+impl Cells {
+    pub fn new() -> Self {
+        Cells::from(Vec::new())
+    }
+
+    pub fn with_span(items: Vec<Expr>, span: Span) -> Self {
+        Cells {
+            items: Arc::new(Storage {
+                items,
+                span: Some(span),
+                code: OnceLock::new(),
+            }),
+            start: 0,
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.items.span
+    }
+
+    /// Everything but the first element, sharing the same storage.
+    pub fn tail(&self) -> Cells {
+        Cells {
+            items: self.items.clone(),
+            start: (self.start + 1).min(self.items.items.len()),
+        }
+    }
+
+    /// Identity of the shared storage, the same for every tail of a list
+    pub(crate) fn storage_id(&self) -> *const () {
+        Arc::as_ptr(&self.items) as *const ()
+    }
+
+    pub(crate) fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.items)
+    }
+
+    /// All elements of the shared storage, including the ones a tail skips
+    pub(crate) fn storage(&self) -> &[Expr] {
+        &self.items.items
+    }
+
+    /// The compiled form of these cells, cached on the storage so a lambda
+    /// body or `if` branch is only compiled once
+    pub(crate) fn code(&self, compile: impl FnOnce(&Cells) -> Arc<Chunk>) -> Arc<Chunk> {
+        if self.start == 0 {
+            self.items.code.get_or_init(|| compile(self)).clone()
+        } else {
+            compile(self)
+        }
+    }
+
+    /// The same elements in storage of their own, so code cached on these
+    /// cells can hold on to them without keeping itself alive
+    pub(crate) fn detached(&self) -> Cells {
+        Cells {
+            items: Arc::new(Storage {
+                items: self.to_vec(),
+                span: self.span(),
+                code: OnceLock::new(),
+            }),
+            start: 0,
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<Expr> {
+        if self.start == 0 {
+            match Arc::try_unwrap(self.items) {
+                Ok(storage) => storage.items,
+                Err(rc) => rc.items.to_vec(),
+            }
+        } else {
+            self.items.items[self.start..].to_vec()
+        }
+    }
+}
+
+impl Default for Cells {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Cells {
+    type Target = [Expr];
+
+    fn deref(&self) -> &[Expr] {
+        &self.items.items[self.start..]
+    }
+}
+
+impl From<Vec<Expr>> for Cells {
+    fn from(items: Vec<Expr>) -> Self {
+        Cells {
+            items: Arc::new(Storage {
+                items,
+                span: None,
+                code: OnceLock::new(),
+            }),
+            start: 0,
+        }
+    }
+}
+
+impl FromIterator<Expr> for Cells {
+    fn from_iter<I: IntoIterator<Item = Expr>>(iter: I) -> Self {
+        Cells::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl IntoIterator for Cells {
+    type Item = Expr;
+    type IntoIter = std::vec::IntoIter<Expr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Cells {
+    type Item = &'a Expr;
+    type IntoIter = std::slice::Iter<'a, Expr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl PartialEq for Cells {
+    fn eq(&self, other: &Cells) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl Hash for Cells {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self[..].hash(state)
+    }
+}
+
+impl fmt::Debug for Cells {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
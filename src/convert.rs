@@ -0,0 +1,301 @@
+//! Conversions between Rust values and `Expr`, for embedders.
+//!
+//! `IntoExpr` turns a Rust value into the jlisp value it stands for,
+//! `FromExpr` goes the other way and fails with the same type error a
+//! builtin raises for an argument of the wrong type. `Interpreter::call`
+//! takes its arguments as `IntoArgs`, a tuple or a `Vec`.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+
+use crate::ast::{Error, Expr, Map};
+use crate::cells::Cells;
+use crate::number::Int;
+use crate::span::Span;
+
+pub trait IntoExpr {
+    fn into_expr(self) -> Expr;
+}
+
+pub trait FromExpr: Sized {
+    /// `expr` as `Self`, or the type error `op` raises for it
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error>;
+}
+
+/// The arguments of a call from Rust, each element of a tuple or a `Vec` is
+/// one argument
+pub trait IntoArgs {
+    fn into_args(self) -> Vec<Expr>;
+}
+
+fn type_error(op: &str, expected: &str, received: String, span: Span) -> Error {
+    Error::IncompatibleType {
+        op: op.to_string(),
+        expected: expected.to_string(),
+        received,
+        span,
+    }
+}
+
+impl IntoExpr for Expr {
+    fn into_expr(self) -> Expr {
+        self
+    }
+}
+
+// what builtins that only do something return
+impl IntoExpr for () {
+    fn into_expr(self) -> Expr {
+        Expr::Sexpr(Cells::new())
+    }
+}
+
+impl FromExpr for () {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        match expr {
+            Expr::Sexpr(cells) if cells.is_empty() => Ok(()),
+            _ => Err(type_error(op, "()", expr.as_str(), span)),
+        }
+    }
+}
+
+impl FromExpr for Expr {
+    fn from_expr(expr: Expr, _op: &str, _span: Span) -> Result<Self, Error> {
+        Ok(expr)
+    }
+}
+
+macro_rules! int_impls {
+    ($($t:ty),*) => {$(
+        impl IntoExpr for $t {
+            fn into_expr(self) -> Expr {
+                match i64::try_from(self) {
+                    Ok(n) => Int::Small(n).into_expr(),
+                    Err(_) => Int::Big(BigInt::from(self)).into_expr(),
+                }
+            }
+        }
+
+        impl FromExpr for $t {
+            fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+                let Some(n) = Int::from_expr(&expr) else {
+                    return Err(type_error(op, "Number", expr.as_str(), span));
+                };
+                let n = n.big();
+                <$t>::try_from(&n).map_err(|_| {
+                    let expected = concat!("Number that fits in ", stringify!($t));
+                    type_error(op, expected, n.to_string(), span)
+                })
+            }
+        }
+    )*};
+}
+
+int_impls!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl IntoExpr for BigInt {
+    fn into_expr(self) -> Expr {
+        Int::Big(self).into_expr()
+    }
+}
+
+impl FromExpr for BigInt {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        match Int::from_expr(&expr) {
+            Some(n) => Ok(n.big()),
+            None => Err(type_error(op, "Number", expr.as_str(), span)),
+        }
+    }
+}
+
+impl IntoExpr for f64 {
+    fn into_expr(self) -> Expr {
+        Expr::Float(self)
+    }
+}
+
+impl IntoExpr for f32 {
+    fn into_expr(self) -> Expr {
+        Expr::Float(self.into())
+    }
+}
+
+// every kind of number converts to a float, like the math builtins take them
+impl FromExpr for f64 {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        match crate::number::Num::from_expr(&expr) {
+            Some(n) => Ok(n.to_f64()),
+            None => Err(type_error(op, "Number or Float", expr.as_str(), span)),
+        }
+    }
+}
+
+impl FromExpr for f32 {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        f64::from_expr(expr, op, span).map(|f| f as f32)
+    }
+}
+
+impl IntoExpr for bool {
+    fn into_expr(self) -> Expr {
+        Expr::Bool(self)
+    }
+}
+
+impl FromExpr for bool {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        match expr {
+            Expr::Bool(b) => Ok(b),
+            _ => Err(type_error(op, "Bool", expr.as_str(), span)),
+        }
+    }
+}
+
+impl IntoExpr for char {
+    fn into_expr(self) -> Expr {
+        Expr::Char(self)
+    }
+}
+
+impl FromExpr for char {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        match expr {
+            Expr::Char(c) => Ok(c),
+            _ => Err(type_error(op, "Char", expr.as_str(), span)),
+        }
+    }
+}
+
+impl IntoExpr for String {
+    fn into_expr(self) -> Expr {
+        Expr::String(self)
+    }
+}
+
+impl IntoExpr for &str {
+    fn into_expr(self) -> Expr {
+        Expr::String(self.to_string())
+    }
+}
+
+impl FromExpr for String {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        expr.into_string(op, span)
+    }
+}
+
+impl<T: IntoExpr> IntoExpr for Option<T> {
+    fn into_expr(self) -> Expr {
+        self.map_or(Expr::Nil, T::into_expr)
+    }
+}
+
+impl<T: FromExpr> FromExpr for Option<T> {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        match expr {
+            Expr::Nil => Ok(None),
+            expr => T::from_expr(expr, op, span).map(Some),
+        }
+    }
+}
+
+impl<T: IntoExpr> IntoExpr for Vec<T> {
+    fn into_expr(self) -> Expr {
+        Expr::List(self.into_iter().map(T::into_expr).collect())
+    }
+}
+
+impl<T: FromExpr> FromExpr for Vec<T> {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        let items = expr.into_list(op, span)?;
+        items
+            .into_iter()
+            .map(|e| T::from_expr(e, op, span))
+            .collect()
+    }
+}
+
+impl<K: IntoExpr, V: IntoExpr> IntoExpr for HashMap<K, V> {
+    fn into_expr(self) -> Expr {
+        let map: Map = self
+            .into_iter()
+            .map(|(k, v)| (k.into_expr(), v.into_expr()))
+            .collect();
+        Expr::Map(Arc::new(map))
+    }
+}
+
+impl<K: FromExpr + Eq + Hash, V: FromExpr> FromExpr for HashMap<K, V> {
+    fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+        let Expr::Map(map) = expr else {
+            return Err(type_error(op, "Map", expr.as_str(), span));
+        };
+        let map = Arc::unwrap_or_clone(map);
+        map.into_iter()
+            .map(|(k, v)| Ok((K::from_expr(k, op, span)?, V::from_expr(v, op, span)?)))
+            .collect()
+    }
+}
+
+impl<T: IntoExpr> IntoArgs for Vec<T> {
+    fn into_args(self) -> Vec<Expr> {
+        self.into_iter().map(T::into_expr).collect()
+    }
+}
+
+impl IntoArgs for () {
+    fn into_args(self) -> Vec<Expr> {
+        Vec::new()
+    }
+}
+
+// tuples are fixed size Lists, and as arguments one argument per element
+macro_rules! tuple_impls {
+    ($($len:literal => ($($name:ident),+)),*) => {$(
+        #[allow(non_snake_case)]
+        impl<$($name: IntoExpr),+> IntoExpr for ($($name,)+) {
+            fn into_expr(self) -> Expr {
+                Expr::List(self.into_args().into())
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<$($name: IntoExpr),+> IntoArgs for ($($name,)+) {
+            fn into_args(self) -> Vec<Expr> {
+                let ($($name,)+) = self;
+                vec![$($name.into_expr()),+]
+            }
+        }
+
+        #[allow(non_snake_case)]
+        impl<$($name: FromExpr),+> FromExpr for ($($name,)+) {
+            fn from_expr(expr: Expr, op: &str, span: Span) -> Result<Self, Error> {
+                let expected = concat!("List of ", $len);
+                let items = match expr {
+                    Expr::List(items) if items.len() == $len => items,
+                    Expr::List(items) => {
+                        return Err(type_error(op, expected, format!("List of {}", items.len()), span));
+                    }
+                    _ => return Err(type_error(op, expected, expr.as_str(), span)),
+                };
+                let mut items = items.into_iter();
+                $(let $name = $name::from_expr(items.next().unwrap(), op, span)?;)+
+                Ok(($($name,)+))
+            }
+        }
+    )*};
+}
+
+tuple_impls!(
+    1 => (A),
+    2 => (A, B),
+    3 => (A, B, C),
+    4 => (A, B, C, D),
+    5 => (A, B, C, D, E),
+    6 => (A, B, C, D, E, F)
+);
@@ -1,79 +1,149 @@
-use slotmap::SlotMap;
-use std::cell::RefCell;
+use std::cell::UnsafeCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, MutexGuard};
 
 use crate::ast::Expr;
+use crate::gc;
+use crate::interpreter::State;
 
-// i got this to work, but honestly dont 100% understand how this slotmap works
-pub type EnvId = slotmap::DefaultKey;
+/// The bindings of one env. They're guarded by the `bindings` lock of the
+/// interpreter the env belongs to rather than a lock of their own, so a
+/// lookup through a chain of scopes locks once.
+struct Bindings(UnsafeCell<HashMap<String, Expr>>);
 
-#[derive(Debug, Clone)]
-struct EnvData {
-    pub map: HashMap<String, Expr>,
-    pub parent: Option<EnvId>,
-}
+// SAFETY: the map is only reached through `Env::locked`, which holds the
+// `bindings` lock of the env's state, and every env of a chain shares it
+unsafe impl Sync for Bindings {}
 
-thread_local! {
-    static ENV_STORAGE: RefCell<SlotMap<EnvId, EnvData>> = RefCell::new(SlotMap::with_key());
+pub(crate) struct EnvData {
+    map: Bindings,
+    pub parent: Option<Env>,
+    // the interpreter the env belongs to
+    pub state: Arc<State>,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Env(EnvId);
+/// A reference counted scope. Envs that only keep each other alive through
+/// their bindings (a closure stored in the env it captured) are reclaimed by
+/// `gc::collect`.
+#[derive(Clone)]
+pub struct Env(pub(crate) Arc<EnvData>);
 
 impl Env {
+    fn alloc(parent: Option<Env>, state: Arc<State>) -> Self {
+        let env = Env(Arc::new(EnvData {
+            map: Bindings(UnsafeCell::new(HashMap::new())),
+            parent,
+            state,
+        }));
+        gc::register(&env);
+        env
+    }
+
+    /// A root env of an interpreter of its own
     pub fn new() -> Self {
-        ENV_STORAGE.with(|storage| {
-            let id = storage.borrow_mut().insert(EnvData {
-                map: HashMap::new(),
-                parent: None,
-            });
-            Env(id)
-        })
+        Self::alloc(None, Arc::default())
+    }
+
+    pub fn child(parent: &Env) -> Self {
+        Self::alloc(Some(parent.clone()), parent.0.state.clone())
+    }
+
+    /// Another root env of the interpreter `self` belongs to
+    pub(crate) fn new_root(&self) -> Self {
+        Self::alloc(None, self.0.state.clone())
     }
 
-    pub fn child(parent: Env) -> Self {
-        ENV_STORAGE.with(|storage| {
-            let id = storage.borrow_mut().insert(EnvData {
-                map: HashMap::new(),
-                parent: Some(parent.0),
-            });
-            Env(id)
-        })
+    pub(crate) fn state(&self) -> &State {
+        &self.0.state
     }
 
+    /// The value bound to `key` here or in an enclosing scope. `ns/name` is
+    /// also `name` in the namespace `import` bound to `ns/`.
     pub fn get(&self, key: &str) -> Option<Expr> {
-        ENV_STORAGE.with(|storage| {
-            let storage = storage.borrow();
-            let mut current = Some(self.0);
-            while let Some(id) = current {
-                let data = &storage[id];
-                if let Some(v) = data.map.get(key) {
-                    return Some(v.clone());
-                }
-                current = data.parent;
+        self.lookup(key).or_else(|| self.qualified(key))
+    }
+
+    fn lookup(&self, key: &str) -> Option<Expr> {
+        let locked = self.locked();
+        let mut current = self;
+        loop {
+            if let Some(v) = locked.map(current).get(key) {
+                return Some(v.clone());
             }
-            None
-        })
+            current = current.0.parent.as_ref()?;
+        }
+    }
+
+    fn qualified(&self, key: &str) -> Option<Expr> {
+        let (ns, name) = key.split_once('/')?;
+        if ns.is_empty() || name.is_empty() {
+            return None;
+        }
+        match self.lookup(&key[..=ns.len()])? {
+            Expr::Map(map) => map.get(&Expr::String(name.to_string())).cloned(),
+            _ => None,
+        }
     }
 
     pub fn insert(&self, key: String, val: Expr) {
-        ENV_STORAGE.with(|storage| {
-            storage.borrow_mut()[self.0].map.insert(key, val);
-        })
+        let old = self.locked().map_mut(self).insert(key, val);
+        // dropping a value can free envs, so not under the lock
+        drop(old);
+    }
+
+    /// Bind every pair in `bindings` at once
+    pub(crate) fn extend(&self, bindings: impl IntoIterator<Item = (String, Expr)>) {
+        let mut old = Vec::new();
+        let mut locked = self.locked();
+        let map = locked.map_mut(self);
+        old.extend(bindings.into_iter().filter_map(|(k, v)| map.insert(k, v)));
+        drop(locked);
+        drop(old);
+    }
+
+    /// Run `f` on the bindings of this scope alone. `f` must not reach any
+    /// env, it runs under the bindings lock.
+    pub(crate) fn with_bindings<R>(&self, f: impl FnOnce(&HashMap<String, Expr>) -> R) -> R {
+        f(self.locked().map(self))
+    }
+
+    /// Unbind everything in this scope, returns what was bound
+    pub(crate) fn take_bindings(&self) -> HashMap<String, Expr> {
+        mem::take(self.locked().map_mut(self))
+    }
+
+    fn locked(&self) -> Locked<'_> {
+        Locked {
+            _guard: self.0.state.bindings.lock().unwrap(),
+        }
     }
 
     pub fn root(&self) -> Env {
-        ENV_STORAGE.with(|storage| {
-            let storage = storage.borrow();
-            let mut current = self.0;
-            loop {
-                let data = &storage[current];
-                match data.parent {
-                    Some(p) => current = p,
-                    None => return Env(current),
-                }
-            }
-        })
+        let mut current = self;
+        while let Some(p) = &current.0.parent {
+            current = p;
+        }
+        current.clone()
+    }
+}
+
+/// Proof that the bindings lock of an interpreter is held
+struct Locked<'a> {
+    _guard: MutexGuard<'a, ()>,
+}
+
+impl Locked<'_> {
+    fn map<'m>(&'m self, env: &'m Env) -> &'m HashMap<String, Expr> {
+        // SAFETY: the lock is held for as long as the borrow lives
+        unsafe { &*env.0.map.0.get() }
+    }
+
+    fn map_mut<'m>(&'m mut self, env: &'m Env) -> &'m mut HashMap<String, Expr> {
+        // SAFETY: as for `map`, and borrowing `self` mutably rules out any
+        // other borrow of a map while this one lives
+        unsafe { &mut *env.0.map.0.get() }
     }
 }
 
@@ -82,3 +152,10 @@ impl Default for Env {
         Self::new()
     }
 }
+
+impl fmt::Debug for Env {
+    // envs are cyclic, so only print the identity
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Env({:p})", Arc::as_ptr(&self.0))
+    }
+}
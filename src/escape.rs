@@ -0,0 +1,62 @@
+//! Escape sequences in string literals.
+//!
+//! `unescape` is what the reader does to the text between the quotes,
+//! `escape` is its inverse for printing, so a printed string reads back as
+//! the same string.
+
+use std::fmt;
+
+/// The string a literal stands for, `\n`, `\t`, `\r`, `\0`, `\"`, `\'`, `\\`
+/// and `\u{...}` are understood
+pub fn unescape(s: &str) -> Result<String, &'static str> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('\\') => '\\',
+            Some('u') => {
+                let rest = chars.as_str();
+                let Some(hex) = rest.strip_prefix('{').and_then(|r| r.split_once('}')) else {
+                    return Err("\\u escape needs its code point in braces");
+                };
+                let c = u32::from_str_radix(hex.0, 16)
+                    .ok()
+                    .filter(|_| (1..=6).contains(&hex.0.len()))
+                    .and_then(char::from_u32)
+                    .ok_or("\\u escape is not a valid code point")?;
+                chars = hex.1.chars();
+                c
+            }
+            _ => return Err("unknown escape in string literal"),
+        });
+    }
+    Ok(out)
+}
+
+/// Writes `s` the way a string literal for it would be written, without the
+/// surrounding quotes
+pub fn escape(s: &str, f: &mut impl fmt::Write) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '\n' => f.write_str("\\n")?,
+            '\t' => f.write_str("\\t")?,
+            '\r' => f.write_str("\\r")?,
+            '\0' => f.write_str("\\0")?,
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
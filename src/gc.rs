@@ -0,0 +1,243 @@
+//! Cycle collector for environments.
+//!
+//! Envs are reference counted, so most of them (the env of a finished lambda
+//! call) are freed as soon as the last value pointing at them goes away. What
+//! refcounting can't free are cycles, e.g. a lambda bound inside the env it
+//! captured. For those every env is registered in the heap of its
+//! interpreter, and `collect` runs a trial deletion over the graph of envs
+//! and the shared values they hold: anything whose refcount is higher than
+//! the number of references coming from inside the graph is held from
+//! outside (the Rust stack, the REPL), and everything not reachable from
+//! those roots is garbage.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::ast::{Expr, Map, Set};
+use crate::cells::Cells;
+use crate::env::{Env, EnvData};
+
+// don't bother collecting before this many envs were created
+const MIN_COLLECT: usize = 10_000;
+
+/// The envs of an interpreter
+pub(crate) struct Heap {
+    envs: Vec<Weak<EnvData>>,
+    freed: usize,
+    next_collect: usize,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Heap {
+            envs: Vec::new(),
+            freed: 0,
+            next_collect: MIN_COLLECT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub live: usize,
+    pub freed: usize,
+}
+
+pub(crate) fn register(env: &Env) {
+    let due = {
+        let mut heap = env.state().heap.lock().unwrap();
+        heap.envs.push(Arc::downgrade(&env.0));
+        heap.envs.len() >= heap.next_collect
+    };
+
+    if due {
+        collect(env);
+    }
+}
+
+/// Forget envs that refcounting already freed
+fn prune(heap: &Mutex<Heap>) {
+    let mut heap = heap.lock().unwrap();
+    let before = heap.envs.len();
+    heap.envs.retain(|env| env.strong_count() > 0);
+    heap.freed += before - heap.envs.len();
+}
+
+/// The envs of the interpreter `env` belongs to
+pub fn stats(env: &Env) -> Stats {
+    let heap = &env.state().heap;
+    prune(heap);
+    let heap = heap.lock().unwrap();
+    Stats {
+        live: heap.envs.len(),
+        freed: heap.freed,
+    }
+}
+
+#[derive(Clone)]
+enum Node {
+    Env(Env),
+    Cells(Cells),
+    Body(Arc<Expr>),
+    Map(Arc<Map>),
+    Set(Arc<Set>),
+}
+
+impl Node {
+    fn id(&self) -> *const () {
+        match self {
+            Node::Env(env) => Arc::as_ptr(&env.0) as *const (),
+            Node::Cells(cells) => cells.storage_id(),
+            Node::Body(body) => Arc::as_ptr(body) as *const (),
+            Node::Map(map) => Arc::as_ptr(map) as *const (),
+            Node::Set(set) => Arc::as_ptr(set) as *const (),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Node::Env(env) => Arc::strong_count(&env.0),
+            Node::Cells(cells) => cells.strong_count(),
+            Node::Body(body) => Arc::strong_count(body),
+            Node::Map(map) => Arc::strong_count(map),
+            Node::Set(set) => Arc::strong_count(set),
+        }
+    }
+
+    fn edges(&self, out: &mut Vec<Node>) {
+        match self {
+            Node::Env(env) => {
+                if let Some(parent) = &env.0.parent {
+                    out.push(Node::Env(parent.clone()));
+                }
+                env.with_bindings(|map| {
+                    for val in map.values() {
+                        refs(val, out);
+                    }
+                });
+            }
+            Node::Cells(cells) => {
+                for val in cells.storage() {
+                    refs(val, out);
+                }
+            }
+            Node::Body(body) => refs(body, out),
+            Node::Map(map) => {
+                for (k, v) in map.iter() {
+                    refs(k, out);
+                    refs(v, out);
+                }
+            }
+            Node::Set(set) => {
+                for v in set.iter() {
+                    refs(v, out);
+                }
+            }
+        }
+    }
+}
+
+/// The envs and shared values directly held by a value
+fn refs(expr: &Expr, out: &mut Vec<Node>) {
+    match expr {
+        Expr::Lambda { env, formals, body } => {
+            out.push(Node::Env(env.clone()));
+            out.push(Node::Cells(formals.clone()));
+            out.push(Node::Body(body.clone()));
+        }
+        Expr::Macro(lambda) => out.push(Node::Body(lambda.clone())),
+        Expr::Map(map) => out.push(Node::Map(map.clone())),
+        Expr::Set(set) => out.push(Node::Set(set.clone())),
+        Expr::Sexpr(cells) | Expr::List(cells) => out.push(Node::Cells(cells.clone())),
+        _ => {}
+    }
+}
+
+/// Free every env of the interpreter `env` belongs to that is only kept
+/// alive by cycles, returns how many envs this freed
+pub fn collect(env: &Env) -> usize {
+    trial_deletion(env, None)
+}
+
+/// `collect` as if `root` was already dropped, so an interpreter that goes
+/// away takes the envs only it kept alive with it
+pub(crate) fn release(root: &Env) {
+    trial_deletion(root, Some(Arc::as_ptr(&root.0) as *const ()));
+}
+
+// `released` is an env whose handle doesn't count as held from outside
+fn trial_deletion(env: &Env, released: Option<*const ()>) -> usize {
+    let heap = &env.state().heap;
+    prune(heap);
+    let freed_before = heap.lock().unwrap().freed;
+
+    let mut nodes: Vec<Node> = heap
+        .lock()
+        .unwrap()
+        .envs
+        .iter()
+        .filter_map(|env| env.upgrade().map(|rc| Node::Env(Env(rc))))
+        .collect();
+    let mut index: HashMap<*const (), usize> =
+        nodes.iter().enumerate().map(|(i, n)| (n.id(), i)).collect();
+
+    // find every node reachable from a registered env and count how often
+    // each one is referenced from inside the graph
+    let mut edges: Vec<Vec<usize>> = Vec::new();
+    let mut internal: Vec<usize> = vec![0; nodes.len()];
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < nodes.len() {
+        nodes[i].edges(&mut out);
+        let mut targets = Vec::with_capacity(out.len());
+        for node in out.drain(..) {
+            let next = nodes.len();
+            let j = *index.entry(node.id()).or_insert(next);
+            if j == next {
+                nodes.push(node);
+                internal.push(0);
+            }
+            internal[j] += 1;
+            targets.push(j);
+        }
+        edges.push(targets);
+        i += 1;
+    }
+
+    // whatever has more references than the graph accounts for (minus the
+    // one held by `nodes`) is held from outside and everything it reaches
+    // is alive
+    let mut alive = vec![false; nodes.len()];
+    let mut stack: Vec<usize> = (0..nodes.len())
+        .filter(|&i| {
+            let released = usize::from(Some(nodes[i].id()) == released);
+            nodes[i].strong_count() - 1 > internal[i] + released
+        })
+        .collect();
+    while let Some(i) = stack.pop() {
+        if !mem::replace(&mut alive[i], true) {
+            stack.extend(edges[i].iter().copied().filter(|&j| !alive[j]));
+        }
+    }
+
+    // clearing the bindings of dead envs breaks their cycles
+    let work = nodes.len() + edges.iter().map(Vec::len).sum::<usize>();
+    let garbage: Vec<Env> = nodes
+        .into_iter()
+        .zip(alive)
+        .filter_map(|(node, alive)| match node {
+            Node::Env(env) if !alive => Some(env),
+            _ => None,
+        })
+        .collect();
+    for env in &garbage {
+        drop(env.take_bindings());
+    }
+    drop(garbage);
+
+    prune(heap);
+    let mut heap = heap.lock().unwrap();
+    heap.next_collect = heap.envs.len() + work.max(MIN_COLLECT);
+    heap.freed - freed_before
+}
@@ -0,0 +1,148 @@
+//! An owned interpreter for embedding jlisp.
+//!
+//! Every `Interpreter` has a global env of its own, and keeps its settings,
+//! its imported modules and the envs its cycle collector tracks in a `State`
+//! every one of its envs points to. So several of them can run side by side
+//! without seeing each other's definitions. Values share their parts by
+//! `Arc` and the bindings of its envs are behind one lock, so an interpreter
+//! and the values it returns can be moved to another thread.
+
+use crate::ast::{self, Error, Expr, Map};
+use crate::builtin::{self, Arity, Type};
+use crate::convert::{FromExpr, IntoArgs};
+use crate::env::Env;
+use crate::gc::{self, Heap};
+use crate::span::Span;
+use crate::vm::Evaluator;
+use std::collections::HashMap;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// What an interpreter keeps besides its bindings, shared by all of its envs
+/// so builtins reach it through the env they are called in
+#[derive(Default)]
+pub(crate) struct State {
+    pub heap: Mutex<Heap>,
+    // guards the bindings of every env of the interpreter
+    pub bindings: Mutex<()>,
+    // read on every eval, so not behind a lock
+    pub tree_walk: AtomicBool,
+    // the namespace of every module imported so far, by canonical path
+    pub modules: Mutex<HashMap<PathBuf, Arc<Map>>>,
+    // the modules being evaluated right now, outermost first
+    pub importing: Mutex<Vec<PathBuf>>,
+}
+
+impl State {
+    pub fn evaluator(&self) -> Evaluator {
+        match self.tree_walk.load(Ordering::Relaxed) {
+            true => Evaluator::TreeWalk,
+            false => Evaluator::Vm,
+        }
+    }
+}
+
+pub struct Interpreter {
+    env: Env,
+}
+
+impl Interpreter {
+    /// An interpreter with the builtins and the prelude
+    pub fn new() -> Self {
+        Interpreter {
+            env: builtin::setup_builtins(),
+        }
+    }
+
+    /// An interpreter with only the builtins
+    pub fn bare() -> Self {
+        Interpreter {
+            env: builtin::setup_builtins_bare(),
+        }
+    }
+
+    /// Run everything this interpreter evaluates on `evaluator`
+    pub fn with_evaluator(self, evaluator: Evaluator) -> Self {
+        self.set_evaluator(evaluator);
+        self
+    }
+
+    pub fn evaluator(&self) -> Evaluator {
+        self.env.state().evaluator()
+    }
+
+    pub fn set_evaluator(&self, evaluator: Evaluator) {
+        let tree_walk = evaluator == Evaluator::TreeWalk;
+        self.env
+            .state()
+            .tree_walk
+            .store(tree_walk, Ordering::Relaxed);
+    }
+
+    /// The global env definitions end up in
+    pub fn env(&self) -> &Env {
+        &self.env
+    }
+
+    /// Every form in `src` evaluated in order, the value of the last
+    pub fn eval_str(&self, src: &str) -> Result<Expr, Error> {
+        self.eval_source("<eval>", src)
+    }
+
+    /// `eval_str` with errors in `src` reported as in the file `name`
+    pub fn eval_source(&self, name: &str, src: &str) -> Result<Expr, Error> {
+        builtin::eval_source(name, src, &self.env, Span::default())
+    }
+
+    /// Every form in the file at `path` evaluated in order, the value of the
+    /// last
+    pub fn eval_file(&self, path: &str) -> Result<Expr, Error> {
+        builtin::eval_file(path, &self.env, Span::default())
+    }
+
+    /// The global `name` called with `args`, a tuple or a `Vec` of values
+    /// that aren't evaluated again, its result converted to `R`
+    pub fn call<A: IntoArgs, R: FromExpr>(&self, name: &str, args: A) -> Result<R, Error> {
+        let span = Span::default();
+        let f = self.env.get(name).ok_or_else(|| Error::UndefinedSymbol {
+            sym: name.to_string(),
+            span,
+        })?;
+        let result = ast::call(&self.env, f, args.into_args(), span)?;
+        R::from_expr(result, name, span)
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Expr> {
+        self.env.get(name)
+    }
+
+    pub fn set_global(&self, name: &str, value: Expr) {
+        self.env.insert(name.to_string(), value);
+    }
+
+    /// Bind `name` to a Rust function, see `builtin::register`
+    pub fn register<F>(&self, name: &str, arity: Arity, types: &[Type], f: F)
+    where
+        F: Fn(Vec<Expr>, Span) -> Result<Expr, Error> + Send + Sync + 'static,
+    {
+        builtin::register(&self.env, name, arity, types, f);
+    }
+}
+
+// the global env and the modules hold closures that point back at them, so
+// refcounting alone never frees an interpreter
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        let modules = mem::take(&mut *self.env.state().modules.lock().unwrap());
+        drop(modules);
+        gc::release(&self.env);
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
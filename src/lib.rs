@@ -1,8 +1,24 @@
 use lalrpop_util::lalrpop_mod;
 
+// so `#[jlisp::builtin]` expands to the same paths in here as in other crates
+extern crate self as jlisp;
+
 pub mod ast;
+pub mod cells;
+pub mod convert;
 
 lalrpop_mod!(pub grammar);
 
 pub mod builtin;
 pub mod env;
+pub(crate) mod escape;
+pub mod gc;
+pub mod interpreter;
+pub(crate) mod number;
+pub mod span;
+pub mod trace;
+pub mod vm;
+
+#[doc(hidden)]
+pub use inventory;
+pub use jlisp_macros::builtin;
@@ -9,6 +9,9 @@ use std::io;
 
 use jlisp::ast::Expr;
 use jlisp::grammar;
+use jlisp::interpreter::Interpreter;
+use jlisp::span::{Source, Span};
+use jlisp::vm::Evaluator;
 
 fn loc_to_line(src: &str, byte: usize) -> String {
     let mut line = 1;
@@ -23,17 +26,18 @@ fn loc_to_line(src: &str, byte: usize) -> String {
     String::from(src.lines().nth(line).unwrap())
 }
 
-fn execute_file(filename: &str) -> io::Result<()> {
+fn execute_file(filename: &str, interp: &Interpreter) -> io::Result<()> {
     let content = fs::read_to_string(filename)?;
-    let env = jlisp::builtin::setup_builtins();
+    let env = interp.env();
     let pe = grammar::JLispParser::new();
+    let source = Source::new(filename, &content);
 
     // Parse and execute each expression in the file
-    match pe.parse(&content) {
+    match pe.parse(&source, &content) {
         Ok(jl) => {
             for expr in jl.exprs {
                 // in Ok just continue
-                if let Err(e) = expr.eval(env, 0) {
+                if let Err(e) = expr.eval(env, Span::file(source.file())) {
                     eprintln!("error during eval: {}", e);
                     break;
                 }
@@ -62,7 +66,7 @@ fn execute_file(filename: &str) -> io::Result<()> {
                 "unexpected extra input: '{}'",
                 &content[(token.0)..(token.2)]
             ),
-            _ => todo!(),
+            ParseError::User { error } => eprintln!("{}", error),
         },
     }
 
@@ -70,16 +74,37 @@ fn execute_file(filename: &str) -> io::Result<()> {
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // run on the tree-walking evaluator instead of the bytecode vm
+    let tree_walk = match args.iter().position(|a| a == "--tree-walk") {
+        Some(i) => {
+            args.remove(i);
+            true
+        }
+        None => false,
+    };
+
+    // start with only the builtins, without map, filter, foldl, ...
+    let interp = match args.iter().position(|a| a == "--no-prelude") {
+        Some(i) => {
+            args.remove(i);
+            Interpreter::bare()
+        }
+        None => Interpreter::new(),
+    };
+    if tree_walk {
+        interp.set_evaluator(Evaluator::TreeWalk);
+    }
 
     // file argument provided, execute it
-    if args.len() > 1 {
-        let _ = execute_file(&args[1]);
+    if let Some(file) = args.first() {
+        let _ = execute_file(file, &interp);
         return Ok(());
     }
 
     //  start the REPL
-    let env = jlisp::builtin::setup_builtins();
+    let env = interp.env();
     let pe = grammar::ExprParser::new();
     let mut rl = DefaultEditor::new()?;
     let repl_hist: String = shellexpand::full("~/.jrepl_hist").unwrap().to_string();
@@ -97,7 +122,8 @@ fn main() -> Result<()> {
             Ok(line) => {
                 rl.add_history_entry(line.as_str())?;
 
-                let expr: Expr = match pe.parse(&line) {
+                let source = Source::new("<repl>", &line);
+                let expr: Expr = match pe.parse(&source, &line) {
                     Ok(v) => v,
                     Err(e) => {
                         println!("failed to parse line: {}\n\tError: {:.?}", line, e);
@@ -105,7 +131,7 @@ fn main() -> Result<()> {
                     }
                 };
 
-                match expr.eval(env, 0) {
+                match expr.eval(env, Span::file(source.file())) {
                     Ok(v) => println!("{}", v),
                     Err(e) => println!("ERROR: {}", e),
                 }
@@ -0,0 +1,485 @@
+//! Numbers: integers without overflow, exact rationals and floats.
+//!
+//! Integers are `Expr::Number` while they fit in an i64 and `Expr::BigInt`
+//! once they don't. Every operation here checks for overflow and redoes the
+//! work on bignums instead of wrapping, and `into_expr` moves results that
+//! fit again back to an i64, so an integer always has exactly one
+//! representation and equality and hashing don't have to care about both.
+//! The same goes for `Expr::Rational`, which is always in lowest terms and
+//! never has a denominator of 1. The primality test and factorization the
+//! number theory builtins use live here too.
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{Pow, Signed, ToPrimitive, Zero};
+
+use crate::ast::{Error, Expr};
+use crate::span::Span;
+
+#[derive(Debug, Clone)]
+pub enum Int {
+    Small(i64),
+    Big(BigInt),
+}
+
+impl Int {
+    pub fn from_expr(e: &Expr) -> Option<Int> {
+        match e {
+            Expr::Number(n) => Some(Int::Small(*n)),
+            Expr::BigInt(b) => Some(Int::Big((**b).clone())),
+            _ => None,
+        }
+    }
+
+    pub fn into_expr(self) -> Expr {
+        match self {
+            Int::Small(n) => Expr::Number(n),
+            Int::Big(b) => match b.to_i64() {
+                Some(n) => Expr::Number(n),
+                None => Expr::BigInt(Arc::new(b)),
+            },
+        }
+    }
+
+    pub fn big(self) -> BigInt {
+        match self {
+            Int::Small(n) => BigInt::from(n),
+            Int::Big(b) => b,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Int::Small(n) => *n == 0,
+            Int::Big(b) => b.is_zero(),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Int::Small(n) => *n as f64,
+            Int::Big(b) => b.to_f64().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// The value as a u32, for exponents and shift amounts
+    pub fn to_u32(&self) -> Option<u32> {
+        match self {
+            Int::Small(n) => u32::try_from(*n).ok(),
+            Int::Big(_) => None,
+        }
+    }
+
+    /// The number of bits of the magnitude, 0 for 0
+    pub fn bits(&self) -> u64 {
+        match self {
+            Int::Small(n) => u64::from(64 - n.unsigned_abs().leading_zeros()),
+            Int::Big(b) => b.bits(),
+        }
+    }
+
+    // `small` on two i64s, `big` on bignums if either isn't one or `small`
+    // overflowed
+    fn op(
+        self,
+        other: Int,
+        small: fn(i64, i64) -> Option<i64>,
+        big: fn(BigInt, BigInt) -> BigInt,
+    ) -> Int {
+        if let (Int::Small(a), Int::Small(b)) = (&self, &other)
+            && let Some(r) = small(*a, *b)
+        {
+            return Int::Small(r);
+        }
+        Int::Big(big(self.big(), other.big()))
+    }
+
+    pub fn add(self, other: Int) -> Int {
+        self.op(other, i64::checked_add, |a, b| a + b)
+    }
+
+    pub fn sub(self, other: Int) -> Int {
+        self.op(other, i64::checked_sub, |a, b| a - b)
+    }
+
+    pub fn mul(self, other: Int) -> Int {
+        self.op(other, i64::checked_mul, |a, b| a * b)
+    }
+
+    /// Truncating division, `other` must not be zero
+    pub fn div(self, other: Int) -> Int {
+        self.op(other, i64::checked_div, |a, b| a / b)
+    }
+
+    /// Remainder with the sign of `self`, `other` must not be zero
+    pub fn rem(self, other: Int) -> Int {
+        self.op(other, i64::checked_rem, |a, b| a % b)
+    }
+
+    pub fn bitand(self, other: Int) -> Int {
+        self.op(other, |a, b| Some(a & b), |a, b| a & b)
+    }
+
+    pub fn bitor(self, other: Int) -> Int {
+        self.op(other, |a, b| Some(a | b), |a, b| a | b)
+    }
+
+    pub fn bitxor(self, other: Int) -> Int {
+        self.op(other, |a, b| Some(a ^ b), |a, b| a ^ b)
+    }
+
+    pub fn neg(self) -> Int {
+        match self {
+            Int::Small(n) => match n.checked_neg() {
+                Some(r) => Int::Small(r),
+                None => Int::Big(-BigInt::from(n)),
+            },
+            Int::Big(b) => Int::Big(-b),
+        }
+    }
+
+    pub fn abs(self) -> Int {
+        if self.is_negative() { self.neg() } else { self }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Int::Small(n) => *n < 0,
+            Int::Big(b) => b.is_negative(),
+        }
+    }
+
+    pub fn pow(self, exp: u32) -> Int {
+        if let Int::Small(n) = self
+            && let Some(r) = n.checked_pow(exp)
+        {
+            return Int::Small(r);
+        }
+        Int::Big(Pow::pow(self.big(), exp))
+    }
+
+    pub fn shl(self, n: u32) -> Int {
+        if let Int::Small(a) = self
+            && let Some(r) = 2i64.checked_pow(n).and_then(|m| a.checked_mul(m))
+        {
+            return Int::Small(r);
+        }
+        Int::Big(self.big() << n)
+    }
+
+    /// Arithmetic shift, rounds towards negative infinity
+    pub fn shr(self, n: u32) -> Int {
+        match self {
+            Int::Small(a) => Int::Small(a >> n.min(63)),
+            Int::Big(b) => Int::Big(b >> n),
+        }
+    }
+}
+
+impl PartialEq for Int {
+    fn eq(&self, other: &Int) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Int {}
+
+impl PartialOrd for Int {
+    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Int {
+    fn cmp(&self, other: &Int) -> Ordering {
+        match (self, other) {
+            (Int::Small(a), Int::Small(b)) => a.cmp(b),
+            (Int::Small(a), Int::Big(b)) => BigInt::from(*a).cmp(b),
+            (Int::Big(a), Int::Small(b)) => a.cmp(&BigInt::from(*b)),
+            (Int::Big(a), Int::Big(b)) => a.cmp(b),
+        }
+    }
+}
+
+/// A number of any kind. Arithmetic on two of them happens in whichever
+/// comes later here, so integers and rationals stay exact until a float
+/// gets involved.
+#[derive(Debug, Clone)]
+pub enum Num {
+    Int(Int),
+    Ratio(BigRational),
+    Float(f64),
+}
+
+impl Num {
+    pub fn from_expr(e: &Expr) -> Option<Num> {
+        match e {
+            Expr::Rational(r) => Some(Num::Ratio((**r).clone())),
+            Expr::Float(f) => Some(Num::Float(*f)),
+            e => Int::from_expr(e).map(Num::Int),
+        }
+    }
+
+    /// `e` as a number, or the error `op` reports for anything else
+    pub fn expect(e: &Expr, op: &str, span: Span) -> Result<Num, Error> {
+        Num::from_expr(e).ok_or_else(|| Error::IncompatibleType {
+            op: op.to_string(),
+            expected: "Number, Rational or Float".to_string(),
+            received: e.as_str(),
+            span,
+        })
+    }
+
+    pub fn into_expr(self) -> Expr {
+        match self {
+            Num::Int(n) => n.into_expr(),
+            Num::Ratio(r) if r.is_integer() => Int::Big(r.to_integer()).into_expr(),
+            Num::Ratio(r) => Expr::Rational(Arc::new(r)),
+            Num::Float(f) => Expr::Float(f),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Num::Int(n) => n.to_f64(),
+            Num::Ratio(r) => r.to_f64().unwrap_or(f64::NAN),
+            Num::Float(f) => *f,
+        }
+    }
+
+    fn into_ratio(self) -> BigRational {
+        match self {
+            Num::Int(n) => BigRational::from_integer(n.big()),
+            Num::Ratio(r) => r,
+            Num::Float(_) => unreachable!("floats don't become rationals"),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Num::Int(n) => n.is_zero(),
+            Num::Ratio(r) => r.is_zero(),
+            Num::Float(f) => *f == 0.0,
+        }
+    }
+
+    fn op(
+        self,
+        other: Num,
+        int: fn(Int, Int) -> Int,
+        ratio: fn(BigRational, BigRational) -> BigRational,
+        float: fn(f64, f64) -> f64,
+    ) -> Num {
+        match (self, other) {
+            (Num::Float(a), b) => Num::Float(float(a, b.to_f64())),
+            (a, Num::Float(b)) => Num::Float(float(a.to_f64(), b)),
+            (Num::Int(a), Num::Int(b)) => Num::Int(int(a, b)),
+            (a, b) => Num::Ratio(ratio(a.into_ratio(), b.into_ratio())),
+        }
+    }
+
+    pub fn add(self, other: Num) -> Num {
+        self.op(other, Int::add, |a, b| a + b, |a, b| a + b)
+    }
+
+    pub fn sub(self, other: Num) -> Num {
+        self.op(other, Int::sub, |a, b| a - b, |a, b| a - b)
+    }
+
+    pub fn mul(self, other: Num) -> Num {
+        self.op(other, Int::mul, |a, b| a * b, |a, b| a * b)
+    }
+
+    /// Exact unless a float is involved, `None` when dividing by zero
+    pub fn div(self, other: Num) -> Option<Num> {
+        if other.is_zero() {
+            return None;
+        }
+        Some(match (self, other) {
+            (Num::Int(a), Num::Int(b)) if a.clone().rem(b.clone()).is_zero() => Num::Int(a.div(b)),
+            (Num::Int(a), Num::Int(b)) => Num::Ratio(BigRational::new(a.big(), b.big())),
+            (a, b) => a.op(
+                b,
+                |_, _| unreachable!("integers are divided above"),
+                |a, b| a / b,
+                |a, b| a / b,
+            ),
+        })
+    }
+
+    pub fn neg(self) -> Num {
+        match self {
+            Num::Int(n) => Num::Int(n.neg()),
+            Num::Ratio(r) => Num::Ratio(-r),
+            Num::Float(f) => Num::Float(-f),
+        }
+    }
+
+    pub fn abs(self) -> Num {
+        match self {
+            Num::Int(n) => Num::Int(n.abs()),
+            Num::Ratio(r) => Num::Ratio(r.abs()),
+            Num::Float(f) => Num::Float(f.abs()),
+        }
+    }
+
+    pub fn pow(self, exp: u32) -> Num {
+        match self {
+            Num::Int(n) => Num::Int(n.pow(exp)),
+            Num::Ratio(r) => Num::Ratio(Pow::pow(r, exp)),
+            Num::Float(f) => Num::Float(f.powf(f64::from(exp))),
+        }
+    }
+
+    /// The smaller of the two, as a float if either is one
+    pub fn min(self, other: Num) -> Num {
+        self.pick(other, Ordering::Less, f64::min)
+    }
+
+    /// The larger of the two, as a float if either is one
+    pub fn max(self, other: Num) -> Num {
+        self.pick(other, Ordering::Greater, f64::max)
+    }
+
+    fn pick(self, other: Num, keep: Ordering, float: fn(f64, f64) -> f64) -> Num {
+        if matches!(self, Num::Float(_)) || matches!(other, Num::Float(_)) {
+            return Num::Float(float(self.to_f64(), other.to_f64()));
+        }
+        if self.partial_cmp(&other) == Some(keep.reverse()) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl PartialEq for Num {
+    fn eq(&self, other: &Num) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Num {
+    fn partial_cmp(&self, other: &Num) -> Option<Ordering> {
+        match (self, other) {
+            (Num::Int(a), Num::Int(b)) => Some(a.cmp(b)),
+            (Num::Float(_), _) | (_, Num::Float(_)) => self.to_f64().partial_cmp(&other.to_f64()),
+            (a, b) => Some(a.clone().into_ratio().cmp(&b.clone().into_ratio())),
+        }
+    }
+}
+
+/// Whether `e` is a number of any kind
+pub fn is_number(e: &Expr) -> bool {
+    matches!(
+        e,
+        Expr::Number(_) | Expr::BigInt(_) | Expr::Rational(_) | Expr::Float(_)
+    )
+}
+
+/// Order two numbers of any kind, `None` for NaN and non-numbers
+pub fn compare(a: &Expr, b: &Expr) -> Option<Ordering> {
+    Num::from_expr(a)?.partial_cmp(&Num::from_expr(b)?)
+}
+
+/// Parse an integer literal of any size
+pub fn parse_int(s: &str) -> Option<Expr> {
+    match s.parse::<i64>() {
+        Ok(n) => Some(Expr::Number(n)),
+        Err(_) => s.parse::<BigInt>().ok().map(|b| Int::Big(b).into_expr()),
+    }
+}
+
+// the Miller-Rabin witnesses, together they make the test exact for every
+// n below 3.3e24 and leave a vanishing chance of a wrong answer above that
+const WITNESSES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Whether `n` is a prime
+pub fn is_prime(n: &BigInt) -> bool {
+    if *n < BigInt::from(2) {
+        return false;
+    }
+    for p in WITNESSES {
+        if *n == BigInt::from(p) {
+            return true;
+        }
+        if (n % p).is_zero() {
+            return false;
+        }
+    }
+
+    let one = BigInt::from(1);
+    let n1 = n - 1u32;
+    let s = n1.trailing_zeros().unwrap();
+    let d = &n1 >> s;
+    'witness: for a in WITNESSES {
+        let mut x = BigInt::from(a).modpow(&d, n);
+        if x == one || x == n1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n1 {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// a nontrivial factor of `n`, which is odd, composite and has no factors
+// below a thousand
+fn pollard_rho(n: &BigInt) -> BigInt {
+    let mut c = BigInt::from(1);
+    loop {
+        let f = |x: &BigInt| (x * x + &c) % n;
+        let (mut x, mut y) = (BigInt::from(2), BigInt::from(2));
+        let mut d = BigInt::from(1);
+        while d == BigInt::from(1) {
+            x = f(&x);
+            y = f(&f(&y));
+            d = (&x - &y).abs().gcd(n);
+        }
+        if d != *n {
+            return d;
+        }
+        c += 1;
+    }
+}
+
+/// The prime factors of `n`, which is positive, smallest first and repeated
+/// as often as they divide it
+pub fn factorize(mut n: BigInt) -> Vec<BigInt> {
+    let mut factors = Vec::new();
+    // small factors by trial division, composites never divide what's left
+    for p in 2u32..1000 {
+        while (&n % p).is_zero() {
+            factors.push(BigInt::from(p));
+            n /= p;
+        }
+        if BigInt::from(p * p) > n {
+            break;
+        }
+    }
+
+    let mut rest = vec![n];
+    while let Some(m) = rest.pop() {
+        if m <= BigInt::from(1) {
+            continue;
+        }
+        if is_prime(&m) {
+            factors.push(m);
+        } else {
+            let d = pollard_rho(&m);
+            rest.push(&m / &d);
+            rest.push(d);
+        }
+    }
+    factors.sort();
+    factors
+}
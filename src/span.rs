@@ -0,0 +1,101 @@
+use std::fmt;
+use std::sync::Mutex;
+
+// names of every file that was parsed, indexed by `FileId`
+static FILES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Interned name of a source file, cheap to copy into every span
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(u32);
+
+impl FileId {
+    pub fn new(name: &str) -> FileId {
+        let mut files = FILES.lock().unwrap();
+        let i = match files.iter().position(|f| f == name) {
+            Some(i) => i,
+            None => {
+                files.push(name.to_string());
+                files.len() - 1
+            }
+        };
+        // 0 is left for forms that weren't parsed from anywhere
+        FileId(i as u32 + 1)
+    }
+
+    pub fn name(self) -> String {
+        match self.0 {
+            0 => "<unknown>".to_string(),
+            i => FILES.lock().unwrap()[i as usize - 1].clone(),
+        }
+    }
+}
+
+/// Where a form was read from, lines and columns start at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub file: FileId,
+    pub line: u32,
+    pub col: u32,
+    pub end_line: u32,
+    pub end_col: u32,
+}
+
+impl Span {
+    /// Somewhere in `file`, before any of its forms
+    pub fn file(file: FileId) -> Span {
+        Span {
+            file,
+            ..Span::default()
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.file.name())
+        } else {
+            write!(f, "{}:{}:{}", self.file.name(), self.line, self.col)
+        }
+    }
+}
+
+/// A file being parsed, turns the parser's byte offsets into spans
+pub struct Source {
+    file: FileId,
+    line_starts: Vec<usize>,
+}
+
+impl Source {
+    pub fn new(name: &str, text: &str) -> Source {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Source {
+            file: FileId::new(name),
+            line_starts,
+        }
+    }
+
+    pub fn file(&self) -> FileId {
+        self.file
+    }
+
+    fn position(&self, byte: usize) -> (u32, u32) {
+        let line = self.line_starts.partition_point(|&start| start <= byte);
+        let col = byte - self.line_starts[line - 1] + 1;
+        (line as u32, col as u32)
+    }
+
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let (line, col) = self.position(start);
+        let (end_line, end_col) = self.position(end);
+        Span {
+            file: self.file,
+            line,
+            col,
+            end_line,
+            end_col,
+        }
+    }
+}
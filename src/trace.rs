@@ -0,0 +1,100 @@
+//! Call stacks for runtime errors.
+//!
+//! Neither evaluator keeps a stack around just for errors. Each lambda call
+//! remembers the form it was called from, and an error records those calls
+//! as it unwinds out of the evaluator. A tail call replaces the call it was
+//! made from, the same way it replaces the frame, so a tail-recursive loop
+//! shows up as a single call.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::ast::Expr;
+use crate::cells::Cells;
+use crate::span::Span;
+
+// how many of the innermost and of the outermost calls a trace keeps
+const KEEP: usize = 10;
+
+/// The form a lambda was called from
+#[derive(Clone)]
+pub(crate) struct CallSite {
+    pub form: Cells,
+    pub span: Span,
+}
+
+/// One call on the stack when an error was raised
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceFrame {
+    /// The symbol the lambda was called through, `<lambda>` for anything else
+    pub name: String,
+    pub span: Span,
+}
+
+impl From<&CallSite> for TraceFrame {
+    fn from(call: &CallSite) -> Self {
+        let name = match call.form.first() {
+            Some(Expr::Symbol(sym)) => sym.clone(),
+            _ => "<lambda>".to_string(),
+        };
+        TraceFrame {
+            name,
+            span: call.span,
+        }
+    }
+}
+
+/// The calls an error unwound through, capped so runaway recursion only
+/// keeps its innermost and outermost calls
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    // both innermost call first
+    inner: Vec<TraceFrame>,
+    outer: VecDeque<TraceFrame>,
+    omitted: usize,
+}
+
+impl Trace {
+    /// Record a call that encloses every call recorded so far
+    pub(crate) fn push_outer(&mut self, frame: TraceFrame) {
+        if self.inner.len() < KEEP {
+            self.inner.push(frame);
+            return;
+        }
+        self.outer.push_back(frame);
+        if self.outer.len() > KEEP {
+            self.outer.pop_front();
+            self.omitted += 1;
+        }
+    }
+
+    /// The kept calls, outermost first
+    pub fn frames(&self) -> impl Iterator<Item = &TraceFrame> {
+        self.outer.iter().rev().chain(self.inner.iter().rev())
+    }
+
+    /// How many calls were dropped from the middle of the stack
+    pub fn omitted(&self) -> usize {
+        self.omitted
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "traceback (most recent call last):")?;
+        for frame in self.outer.iter().rev() {
+            write!(f, "\n  in '{}' at {}", frame.name, frame.span)?;
+        }
+        if self.omitted > 0 {
+            write!(f, "\n  ... {} more calls", self.omitted)?;
+        }
+        for frame in self.inner.iter().rev() {
+            write!(f, "\n  in '{}' at {}", frame.name, frame.span)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+//! Bytecode evaluator.
+//!
+//! Forms are compiled lazily: `compile` turns the form being evaluated into a
+//! `Chunk`, and the cells of lambda bodies and `if` branches cache their
+//! chunk, so they're only compiled the first time they run. Calls to lambdas
+//! and tail steps handed back by builtins push frames instead of recursing.
+
+mod compiler;
+
+use std::sync::Arc;
+
+use crate::ast::{Error, Expr, Step, apply_lambda, expand_macro};
+use crate::env::Env;
+use crate::span::Span;
+use crate::trace::CallSite;
+
+pub use compiler::{Chunk, Op, compile};
+
+/// Which evaluator `Expr::eval` runs on, set per interpreter
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Evaluator {
+    #[default]
+    Vm,
+    TreeWalk,
+}
+
+struct Frame {
+    chunk: Arc<Chunk>,
+    ip: usize,
+    env: Env,
+    // span of the call that entered this frame, for code that wasn't parsed
+    span: Span,
+    // the lambda call this frame is running the body of
+    call: Option<CallSite>,
+}
+
+struct Vm {
+    stack: Vec<Expr>,
+    frames: Vec<Frame>,
+}
+
+impl Vm {
+    /// Continue with whatever a builtin or lambda application handed back,
+    /// `call` is set when entering a lambda body
+    fn step(&mut self, step: Step, tail: bool, span: Span, call: Option<CallSite>) {
+        match step {
+            Step::Done(v) => self.stack.push(v),
+            Step::Eval(expr, env) => {
+                let mut frame = Frame {
+                    chunk: compile(&expr),
+                    ip: 0,
+                    env,
+                    span,
+                    call,
+                };
+                if tail {
+                    let current = self.frames.last_mut().unwrap();
+                    // a builtin's tail step stays in the current call
+                    if frame.call.is_none() {
+                        frame.call = current.call.take();
+                    }
+                    *current = frame;
+                } else {
+                    self.frames.push(frame);
+                }
+            }
+        }
+    }
+
+    fn run(&mut self) -> Result<Expr, Error> {
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+            let op = frame.chunk.code[frame.ip];
+            let span = frame.chunk.spans[frame.ip].unwrap_or(frame.span);
+            frame.ip += 1;
+
+            match op {
+                Op::Const(i) => self.stack.push(frame.chunk.consts[i].clone()),
+                Op::Load(i) => {
+                    let Expr::Symbol(sym) = &frame.chunk.consts[i] else {
+                        panic!();
+                    };
+                    match frame.env.get(sym) {
+                        Some(v) => self.stack.push(v),
+                        None => {
+                            return Err(Error::UndefinedSymbol {
+                                sym: sym.clone(),
+                                span,
+                            });
+                        }
+                    }
+                }
+                Op::LoadOrCall(i) => {
+                    let Expr::Symbol(sym) = &frame.chunk.consts[i] else {
+                        panic!();
+                    };
+                    match frame.env.get(sym) {
+                        Some(Expr::Builtin(b)) if b.is_nullary() => {
+                            let step = b.call(&frame.env, Vec::new(), span)?;
+                            self.step(step, false, span, None);
+                        }
+                        Some(v) => self.stack.push(v),
+                        None => {
+                            return Err(Error::UndefinedSymbol {
+                                sym: sym.clone(),
+                                span,
+                            });
+                        }
+                    }
+                }
+                Op::Special { form, end, tail } => {
+                    if let Some(Expr::Builtin(b)) = self.stack.last()
+                        && b.is_special_form()
+                    {
+                        let Some(Expr::Builtin(b)) = self.stack.pop() else {
+                            panic!();
+                        };
+                        let Expr::Sexpr(form) = &frame.chunk.consts[form] else {
+                            panic!();
+                        };
+                        frame.ip = end;
+                        let step = b.call(&frame.env, form[1..].to_vec(), span)?;
+                        self.step(step, tail, span, None);
+                    } else if let Some(Expr::Macro(_)) = self.stack.last() {
+                        let op = self.stack.pop().unwrap();
+                        let Expr::Sexpr(form) = &frame.chunk.consts[form] else {
+                            panic!();
+                        };
+                        frame.ip = end;
+                        let site = CallSite {
+                            form: form.clone(),
+                            span,
+                        };
+                        let expansion = expand_macro(op, form[1..].to_vec(), &site)?;
+                        let step = Step::Eval(expansion.into_form(), frame.env.clone());
+                        self.step(step, tail, span, None);
+                    }
+                }
+                Op::Call { argc, tail, form } => {
+                    let args = self.stack.split_off(self.stack.len() - argc);
+                    match self.stack.pop().unwrap() {
+                        Expr::Builtin(b) => {
+                            let step = b.call(&frame.env, args, span)?;
+                            self.step(step, tail, span, None);
+                        }
+                        op @ Expr::Lambda { .. } => {
+                            let Expr::Sexpr(form) = &frame.chunk.consts[form] else {
+                                panic!();
+                            };
+                            let site = CallSite {
+                                form: form.clone(),
+                                span,
+                            };
+                            let step = apply_lambda(op, args, span)
+                                .map_err(|err| err.called_from(&site))?;
+                            self.step(step, tail, span, Some(site));
+                        }
+                        _ => return Err(Error::MissingOperator { span }),
+                    }
+                }
+                Op::Return => {
+                    self.frames.pop();
+                    if self.frames.is_empty() {
+                        return Ok(self.stack.pop().unwrap());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Evaluate an expression on the vm
+pub fn eval(expr: Expr, env: &Env, span: Span) -> Result<Expr, Error> {
+    let mut vm = Vm {
+        stack: Vec::new(),
+        frames: vec![Frame {
+            chunk: compile(&expr),
+            ip: 0,
+            env: env.clone(),
+            span,
+            call: None,
+        }],
+    };
+    vm.run().map_err(|err| {
+        // record the calls the error was raised in, innermost first
+        vm.frames
+            .iter()
+            .rev()
+            .filter_map(|frame| frame.call.as_ref())
+            .fold(err, |err, call| err.called_from(call))
+    })
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use crate::ast::Expr;
+use crate::cells::Cells;
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    /// Push `consts[i]`
+    Const(usize),
+    /// Push the value bound to the symbol `consts[i]`
+    Load(usize),
+    /// A lone `(sym)`: push its value, or call it if it's a nullary builtin
+    LoadOrCall(usize),
+    /// If the operator on top of the stack is a special form or a macro, call
+    /// it with the unevaluated args of the form in `consts[form]` and
+    /// continue at `end`
+    Special {
+        form: usize,
+        end: usize,
+        tail: bool,
+    },
+    /// Call the operator below the top `argc` values, `consts[form]` is the
+    /// form being called for traces
+    Call {
+        argc: usize,
+        tail: bool,
+        form: usize,
+    },
+    Return,
+}
+
+/// Bytecode for a single form, always leaves exactly one value on the stack
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<Op>,
+    pub consts: Vec<Expr>,
+    /// The span of the form each op was compiled from, if it was parsed
+    pub spans: Vec<Option<Span>>,
+    // span of the form currently being compiled
+    span: Option<Span>,
+}
+
+impl Chunk {
+    fn constant(&mut self, e: Expr) -> usize {
+        self.consts.push(e);
+        self.consts.len() - 1
+    }
+
+    fn emit(&mut self, op: Op) -> usize {
+        self.code.push(op);
+        self.spans.push(self.span);
+        self.code.len() - 1
+    }
+
+    fn expr(&mut self, e: &Expr, tail: bool) {
+        match e {
+            Expr::Symbol(sym) => {
+                let i = self.constant(Expr::Symbol(sym.clone()));
+                self.emit(Op::Load(i));
+            }
+            Expr::Comment(_) => {
+                let i = self.constant(Expr::Sexpr(Cells::new()));
+                self.emit(Op::Const(i));
+            }
+            Expr::Sexpr(cells) => self.sexpr(cells, tail),
+            _ => {
+                let i = self.constant(e.clone());
+                self.emit(Op::Const(i));
+            }
+        }
+    }
+
+    fn sexpr(&mut self, cells: &Cells, tail: bool) {
+        let outer = self.span;
+        self.span = cells.span().or(outer);
+        match &cells[..] {
+            [] => {
+                let i = self.constant(Expr::Sexpr(Cells::new()));
+                self.emit(Op::Const(i));
+            }
+            [Expr::Symbol(sym)] => {
+                let i = self.constant(Expr::Symbol(sym.clone()));
+                self.emit(Op::LoadOrCall(i));
+            }
+            [single] => self.expr(single, tail),
+            [op, args @ ..] => {
+                self.expr(op, false);
+                // the chunk may get cached on `cells`, which must not keep it alive
+                let form = self.constant(Expr::Sexpr(cells.detached()));
+                let special = self.emit(Op::Special { form, end: 0, tail });
+                for arg in args {
+                    self.expr(arg, false);
+                }
+                self.emit(Op::Call {
+                    argc: args.len(),
+                    tail,
+                    form,
+                });
+                self.code[special] = Op::Special {
+                    form,
+                    end: self.code.len(),
+                    tail,
+                };
+            }
+        }
+        self.span = outer;
+    }
+}
+
+/// Compile an expression into a chunk that evaluates it
+pub fn compile(e: &Expr) -> Arc<Chunk> {
+    match e {
+        Expr::Sexpr(cells) => cells.code(compile_sexpr),
+        _ => {
+            let mut chunk = Chunk::default();
+            chunk.expr(e, true);
+            chunk.emit(Op::Return);
+            Arc::new(chunk)
+        }
+    }
+}
+
+fn compile_sexpr(cells: &Cells) -> Arc<Chunk> {
+    let mut chunk = Chunk::default();
+    chunk.sexpr(cells, true);
+    chunk.emit(Op::Return);
+    Arc::new(chunk)
+}
@@ -0,0 +1,102 @@
+mod common;
+use common::{eval_str, run};
+use jlisp::ast::Expr;
+
+#[test]
+fn test_i64_range() {
+    assert_eq!(run("(* 65536 65536)"), "4294967296");
+    assert_eq!(run("9223372036854775807"), "9223372036854775807");
+    assert_eq!(run("-9223372036854775808"), "-9223372036854775808");
+    assert_eq!(
+        eval_str("(+ 2147483647 1)").unwrap(),
+        Expr::Number(2147483648)
+    );
+}
+
+#[test]
+fn test_overflow_promotes() {
+    assert_eq!(run("(+ 9223372036854775807 1)"), "9223372036854775808");
+    assert_eq!(run("(- -9223372036854775808 1)"), "-9223372036854775809");
+    assert_eq!(run("(- -9223372036854775808)"), "9223372036854775808");
+    assert_eq!(
+        run("(* 9223372036854775807 9223372036854775807)"),
+        "85070591730234615847396907784232501249"
+    );
+    assert_eq!(run("(** 2 100)"), "1267650600228229401496703205376");
+    assert_eq!(run("(<< 1 70)"), "1180591620717411303424");
+    assert_eq!(run("(** 3 4)"), "81");
+    // char arithmetic is on integers too
+    assert_eq!(
+        run("(* 'ÿ' 'ÿ' 'ÿ' 'ÿ' 'ÿ' 'ÿ' 'ÿ' 'ÿ' 'ÿ')"),
+        "4558916353692287109375"
+    );
+    assert_eq!(run("(- 'a' 'b')"), "-1");
+    assert_eq!(run("(/ 'd' 'b')"), "1");
+}
+
+#[test]
+fn test_bignums_shrink_back() {
+    assert_eq!(
+        eval_str("(- (+ 9223372036854775807 10) 10)").unwrap(),
+        Expr::Number(i64::MAX)
+    );
+    assert_eq!(eval_str("(>> (<< 1 70) 69)").unwrap(), Expr::Number(2));
+    assert_eq!(run("(/ (** 2 100) (** 2 98))"), "4");
+    assert_eq!(run("(% (+ (** 10 30) 7) 10)"), "7");
+}
+
+#[test]
+fn test_big_literals() {
+    assert_eq!(
+        run("123456789012345678901234567890"),
+        "123456789012345678901234567890"
+    );
+    assert_eq!(
+        run("(== 99999999999999999999 (+ 99999999999999999998 1))"),
+        "true"
+    );
+    assert_eq!(
+        run("(== 9223372036854775807 (- 9223372036854775808 1))"),
+        "true"
+    );
+}
+
+#[test]
+fn test_compare_and_sort() {
+    assert_eq!(run("(< 9223372036854775807 9223372036854775808)"), "true");
+    assert_eq!(run("(> -99999999999999999999 -5)"), "false");
+    assert_eq!(
+        run("(sort [99999999999999999999 3 -99999999999999999999 -1])"),
+        "[-99999999999999999999 -1 3 99999999999999999999]"
+    );
+    assert_eq!(run("(max 5 (** 2 64))"), "18446744073709551616");
+    assert_eq!(run("(abs (- 0 (** 2 64)))"), "18446744073709551616");
+}
+
+#[test]
+fn test_int_parses_any_size() {
+    assert_eq!(run("(int \"42\")"), "42");
+    assert_eq!(
+        run("(int \"-340282366920938463463374607431768211456\")"),
+        "-340282366920938463463374607431768211456"
+    );
+    assert!(eval_str("(int \"12a\")").is_err());
+}
+
+#[test]
+fn test_arith_type_errors() {
+    assert!(eval_str("(+ 1 \"a\")").is_err());
+    assert!(eval_str("(* 2 [3])").is_err());
+    assert!(eval_str("(** 0 -1)").is_err());
+    assert!(eval_str("(<< 1 -1)").is_err());
+    // results are computed in one go, so huge ones are refused up front
+    assert!(eval_str("(** 2 4000000000)").is_err());
+    assert!(eval_str("(<< 1 4000000000)").is_err());
+    assert!(eval_str("(** 1/2 4000000000)").is_err());
+    assert!(eval_str("(<< (** 2 1000000) 100000)").is_err());
+    assert_eq!(run("(** 1 4000000000)"), "1");
+    assert_eq!(run("(** -1 4000000001)"), "-1");
+    assert_eq!(run("(<< 0 4000000000)"), "0");
+    assert_eq!(run("(len (str (** 2 100000)))"), "30103");
+    assert!(eval_str("(% (** 2 80) 0)").is_err());
+}
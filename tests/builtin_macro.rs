@@ -0,0 +1,86 @@
+use jlisp::ast::{Error, Expr};
+use jlisp::builtin::Arity;
+use jlisp::env::Env;
+use jlisp::interpreter::Interpreter;
+use jlisp::span::Span;
+
+#[jlisp::builtin(name = "scale")]
+fn scale(n: i64, by: f64) -> f64 {
+    n as f64 * by
+}
+
+#[jlisp::builtin]
+fn greet_all(greeting: String, #[rest] names: Vec<String>) -> Vec<String> {
+    names
+        .into_iter()
+        .map(|name| format!("{greeting}, {name}"))
+        .collect()
+}
+
+#[jlisp::builtin(name = "checked-div")]
+fn checked_div(a: i64, b: i64, span: Span) -> Result<i64, Error> {
+    if b == 0 {
+        return Err(Error::DivisionByZero { span });
+    }
+    Ok(a / b)
+}
+
+#[jlisp::builtin(name = "bound?")]
+fn is_bound(env: &Env, name: String) -> bool {
+    env.get(&name).is_some()
+}
+
+fn eval(input: &str) -> Result<Expr, Error> {
+    Interpreter::new().eval_str(input)
+}
+
+#[test]
+fn registered_in_every_interpreter() {
+    assert_eq!(eval("(scale 2 1.5)").unwrap(), Expr::Float(3.0));
+    assert_eq!(
+        Interpreter::bare().eval_str("(checked-div 7 2)").unwrap(),
+        Expr::Number(3)
+    );
+}
+
+#[test]
+fn name_defaults_to_the_function_name() {
+    assert_eq!(
+        eval(r#"(greet-all "hi" "ann" "bo")"#).unwrap().to_string(),
+        r#"["hi, ann" "hi, bo"]"#
+    );
+    assert_eq!(eval(r#"(greet-all "hi")"#).unwrap().to_string(), "[]");
+}
+
+#[test]
+fn arity_comes_from_the_signature() {
+    let err = eval("(scale 2)").unwrap_err();
+    assert_eq!(err.kind(), "arity");
+    let Some(Expr::Builtin(b)) = Interpreter::new().get_global("greet-all") else {
+        panic!("greet-all isn't a builtin");
+    };
+    assert_eq!(b.arity(), Arity::AtLeast(1));
+    // span and env parameters aren't arguments
+    let err = eval("(checked-div 1 2 3)").unwrap_err();
+    assert_eq!(err.kind(), "arity");
+    assert_eq!(eval(r#"(bound? "scale")"#).unwrap(), Expr::Bool(true));
+    assert_eq!(eval(r#"(bound? "nope")"#).unwrap(), Expr::Bool(false));
+}
+
+#[test]
+fn types_come_from_the_signature() {
+    let err = eval(r#"(scale "2" 1.5)"#).unwrap_err();
+    assert_eq!(err.kind(), "type");
+    let err = eval(r#"(greet-all "hi" "ann" 3)"#).unwrap_err();
+    assert_eq!(err.kind(), "type");
+}
+
+#[test]
+fn errors_from_the_function_are_raised() {
+    let err = eval("(checked-div 1 0)").unwrap_err();
+    assert_eq!(err.kind(), "division-by-zero");
+    assert_eq!(
+        eval("(try (checked-div 1 0) (catch e (get e \"kind\")))").unwrap(),
+        Expr::String("division-by-zero".to_string())
+    );
+}
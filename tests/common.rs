@@ -1,28 +1,23 @@
+#![allow(dead_code)]
+
 use jlisp::ast::Expr;
-use jlisp::env::Env;
-use std::cell::RefCell;
+use jlisp::interpreter::Interpreter;
 
-thread_local! {
-    static PERSISTENT_ENV: RefCell<Env> = RefCell::new(jlisp::builtin::setup_builtins());
+pub fn eval_str(input: &str) -> Result<Expr, Box<dyn std::error::Error + '_>> {
+    Ok(Interpreter::new().eval_source("<test>", input)?)
 }
 
-pub fn eval_str(input: &str) -> Result<Expr, Box<dyn std::error::Error + '_>> {
-    let env = jlisp::builtin::setup_builtins();
-    let pe = jlisp::grammar::ExprParser::new();
-    let pres = pe.parse(input)?;
-    let res = pres.eval(env, 0)?;
-    Ok(res)
+/// `input` evaluated in `interp`, so definitions carry over between calls
+pub fn eval_in(interp: &Interpreter, input: &str) -> Result<Expr, Box<dyn std::error::Error>> {
+    Ok(interp.eval_source("<test>", input)?)
 }
 
-pub fn eval_str_persistent(input: &str) -> Result<Expr, Box<dyn std::error::Error + '_>> {
-    let pe = jlisp::grammar::ExprParser::new();
-    let pres = pe.parse(input)?;
-    let res = PERSISTENT_ENV.with(|env| pres.eval(*env.borrow(), 0))?;
-    Ok(res)
+/// `input` evaluated, printed the way the REPL prints it
+pub fn run(input: &str) -> String {
+    eval_str(input).unwrap().to_string()
 }
 
-pub fn reset_persistent_env() {
-    PERSISTENT_ENV.with(|env| {
-        *env.borrow_mut() = jlisp::builtin::setup_builtins();
-    });
+/// `run` in `interp`
+pub fn run_in(interp: &Interpreter, input: &str) -> String {
+    eval_in(interp, input).unwrap().to_string()
 }
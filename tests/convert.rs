@@ -0,0 +1,98 @@
+use jlisp::ast::{Error, Expr};
+use jlisp::convert::{FromExpr, IntoExpr};
+use jlisp::interpreter::Interpreter;
+use jlisp::span::Span;
+use std::collections::HashMap;
+
+fn back<T: FromExpr>(expr: Expr) -> Result<T, Error> {
+    T::from_expr(expr, "convert", Span::default())
+}
+
+fn round_trip<T: IntoExpr + FromExpr + Clone + PartialEq + std::fmt::Debug>(value: T) {
+    assert_eq!(back::<T>(value.clone().into_expr()).unwrap(), value);
+}
+
+#[test]
+fn test_scalars() {
+    assert_eq!(42i32.into_expr().to_string(), "42");
+    assert_eq!(u64::MAX.into_expr().to_string(), "18446744073709551615");
+    assert_eq!(1.5f64.into_expr().to_string(), "1.5");
+    assert_eq!("hi".into_expr().to_string(), "\"hi\"");
+    assert_eq!('x'.into_expr().to_string(), "'x'");
+    round_trip(-7i8);
+    round_trip(u128::MAX);
+    round_trip(usize::MAX);
+    round_trip(2.25f64);
+    round_trip(true);
+    round_trip('λ');
+    round_trip("text".to_string());
+    // any kind of number is a float
+    assert_eq!(back::<f64>(Expr::Number(3)).unwrap(), 3.0);
+    // integers that fit in 64 bits aren't bignums
+    assert_eq!(42u64.into_expr(), Expr::Number(42));
+    assert_eq!(i128::from(i64::MIN).into_expr(), Expr::Number(i64::MIN));
+    assert!(matches!(u64::MAX.into_expr(), Expr::BigInt(_)));
+    round_trip(());
+    assert!(back::<()>(Expr::Nil).is_err());
+}
+
+#[test]
+fn test_containers() {
+    round_trip(vec![1i64, 2, 3]);
+    round_trip(Some(vec!["a".to_string()]));
+    round_trip(None::<i64>);
+    round_trip((1i64, "b".to_string(), 2.5f64));
+    round_trip(HashMap::from([
+        ("x".to_string(), 1i64),
+        ("y".to_string(), 2),
+    ]));
+    assert_eq!(vec![(1i64, true)].into_expr().to_string(), "[[1 true]]");
+    assert_eq!(Some(3i64).into_expr().to_string(), "3");
+    assert_eq!(None::<i64>.into_expr().to_string(), "nil");
+}
+
+#[test]
+fn test_conversion_errors() {
+    let err = back::<i64>(Expr::String("1".into())).unwrap_err();
+    assert!(
+        matches!(&err, Error::IncompatibleType { op, expected, received, .. }
+            if op == "convert" && expected == "Number" && received == "String"),
+        "{err}"
+    );
+    let err = back::<u8>(Expr::Number(300)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "type error in 'convert' expected Number that fits in u8, got 300 at <unknown>"
+    );
+    assert!(back::<u32>(Expr::Number(-1)).is_err());
+    assert!(back::<(i64, i64)>(vec![1i64].into_expr()).is_err());
+    assert!(back::<Vec<i64>>(vec!["a"].into_expr()).is_err());
+    assert!(back::<bool>(Expr::Nil).is_err());
+}
+
+#[test]
+fn test_typed_call() {
+    let interp = Interpreter::new();
+    interp
+        .eval_str("(fun [solve xs n] [map (\\ [x] [* x n]) xs])")
+        .unwrap();
+    let input = vec![1i64, 2, 3];
+    let out: Vec<i64> = interp.call("solve", (input, 2)).unwrap();
+    assert_eq!(out, vec![2, 4, 6]);
+
+    let plus = interp.get_global("+").unwrap();
+    let total: i64 = interp.call("foldl", (plus, 0, vec![1, 2, 3])).unwrap();
+    assert_eq!(total, 6);
+
+    let upper: String = interp.call("upper", ("shout",)).unwrap();
+    assert_eq!(upper, "SHOUT");
+    let found: Option<String> = interp.call("re-match", (r"\d+", "no digits")).unwrap();
+    assert_eq!(found, None);
+
+    // a result of the wrong type is a type error in the called function
+    let err = interp.call::<_, i64>("upper", ("x",)).unwrap_err();
+    assert!(
+        matches!(&err, Error::IncompatibleType { op, .. } if op == "upper"),
+        "{err}"
+    );
+}
@@ -0,0 +1,149 @@
+use jlisp::ast::Error;
+mod common;
+use common::{eval_str, run};
+
+#[test]
+fn test_try_without_error() {
+    assert_eq!(run("(try (+ 1 2))"), "3");
+    assert_eq!(run("(try 1 2 (catch e 3))"), "2");
+}
+
+#[test]
+fn test_catch_kinds() {
+    assert_eq!(
+        run(r#"(try (undefined) (catch e (get e "kind")))"#),
+        "\"undefined-symbol\""
+    );
+    assert_eq!(
+        run(r#"(try (/ 1 0) (catch e (get e "kind")))"#),
+        "\"division-by-zero\""
+    );
+    assert_eq!(
+        run(r#"(try (head 1) (catch e (get e "kind")))"#),
+        "\"type\""
+    );
+    assert_eq!(
+        run(r#"(try (load "no/such/file.jl") (catch e (get e "kind")))"#),
+        "\"io\""
+    );
+    assert_eq!(
+        run(r#"(try (error "boom") (catch e (get e "kind")))"#),
+        "\"user\""
+    );
+    assert_eq!(
+        run(r#"(try (isqrt -4) (catch e (get e "kind")))"#),
+        "\"domain\""
+    );
+    assert_eq!(
+        run(r#"(try (** 0 -1) (catch e (get e "kind")))"#),
+        "\"domain\""
+    );
+    assert_eq!(
+        run(r#"(try (repeat "a" -1) (catch e (get e "kind")))"#),
+        "\"domain\""
+    );
+    assert_eq!(
+        run(r#"(try (replace "a" "" "b") (catch e (get e "kind")))"#),
+        "\"domain\""
+    );
+    assert_eq!(
+        run(r#"(try (int->char -1) (catch e (get e "kind")))"#),
+        "\"domain\""
+    );
+    assert_eq!(
+        run(r#"(try (str-sub "abc" 2 9) (catch e (get e "kind")))"#),
+        "\"domain\""
+    );
+    assert_eq!(
+        run(r#"(try (format "{}" 1 2) (catch e (get e "kind")))"#),
+        "\"arity\""
+    );
+    assert_eq!(
+        run(r#"(try (format "{:x}" 1.5) (catch e (get e "kind")))"#),
+        "\"type\""
+    );
+    assert_eq!(
+        run(r#"(try (format "{" 1) (catch e (get e "kind")))"#),
+        "\"parse\""
+    );
+}
+
+#[test]
+fn test_catch_value() {
+    assert_eq!(
+        run(r#"(try (error "boom") (catch e (get e "message")))"#),
+        "\"boom\""
+    );
+    assert_eq!(
+        run(r#"(try (error "boom") (catch e (get e "payload")))"#),
+        "nil"
+    );
+    assert_eq!(
+        run(r#"(try (error "bad input" {"line" 4}) (catch e (get (get e "payload") "line")))"#),
+        "4"
+    );
+    assert_eq!(
+        run("(try\n  (undefined)\n  (catch e (get e \"line\")))"),
+        "2"
+    );
+    assert_eq!(
+        run(r#"(try (undefined) (catch e (get e "message")))"#),
+        "\"undefined symbol 'undefined'\""
+    );
+}
+
+#[test]
+fn test_error_from_lambda_is_caught() {
+    assert_eq!(
+        run(
+            r#"(do (fun [check x] [if (> x 0) [x] [error "negative" x]]) (try (check -3) (catch e (get e "payload"))))"#
+        ),
+        "-3"
+    );
+}
+
+#[test]
+fn test_finally() {
+    assert_eq!(
+        run("(do (def [n] 0) (try (+ 1 1) (finally (= [n] 1))) n)"),
+        "1"
+    );
+    assert_eq!(
+        run("(do (def [n] 0) (try (undefined) (catch e 5) (finally (= [n] 1))) n)"),
+        "1"
+    );
+    assert_eq!(run("(try 7 (catch e 5) (finally 9))"), "7");
+    // uncaught errors still run the cleanup, then propagate
+    assert!(matches!(
+        eval_str("(try (undefined) (finally 1))")
+            .unwrap_err()
+            .downcast_ref::<Error>(),
+        Some(Error::UndefinedSymbol { .. })
+    ));
+    // an error in the cleanup wins
+    assert!(matches!(
+        eval_str(r#"(try 1 (finally (error "cleanup")))"#)
+            .unwrap_err()
+            .downcast_ref::<Error>()
+            .map(Error::root),
+        Some(Error::User { .. })
+    ));
+}
+
+#[test]
+fn test_error_in_handler_propagates() {
+    let err = eval_str(r#"(try (undefined) (catch e (error "again")))"#).unwrap_err();
+    let err = err.downcast_ref::<Error>().unwrap();
+    assert_eq!(err.kind(), "user");
+    assert_eq!(err.message(), "again");
+}
+
+#[test]
+fn test_error_is_not_a_parse_error() {
+    let err = eval_str(r#"(error "boom")"#).unwrap_err();
+    let err = err.downcast_ref::<Error>().unwrap();
+    assert!(matches!(err.root(), Error::User { .. }));
+    assert!(err.to_string().starts_with("error: boom"), "{err}");
+    assert!(eval_str(r#"(error "a" 1 2)"#).is_err());
+    assert!(eval_str("(error 1)").is_err());
+}
@@ -0,0 +1,5 @@
+; imports its helper next to it, not next to whoever imports it
+(import util/square)
+
+(def [pi] 3.0)
+(def [area] (\ [r] [* pi (square/square r)]))
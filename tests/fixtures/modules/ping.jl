@@ -0,0 +1,2 @@
+(import pong)
+(def [ping] 1)
@@ -0,0 +1,2 @@
+(import ping)
+(def [pong] 2)
@@ -0,0 +1,4 @@
+; imports util/square too, it is evaluated once for both
+(import util/square [square])
+
+(def [box] (\ [w] [square w]))
@@ -0,0 +1 @@
+(def [square] (\ [x] [* x x]))
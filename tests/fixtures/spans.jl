@@ -0,0 +1,5 @@
+; helpers for tests/spans.rs, the line and column numbers matter
+(fun [half x]
+  [/ x 0])
+(fun [broken x]
+  [head x])
@@ -0,0 +1,45 @@
+mod common;
+use common::{eval_str, run};
+use jlisp::ast::Expr;
+
+#[test]
+fn test_double_precision() {
+    assert_eq!(run("0.1"), "0.1");
+    assert_eq!(run("(+ 0.1 0.2)"), "0.30000000000000004");
+    assert_eq!(run("123456789.123"), "123456789.123");
+    assert_eq!(
+        eval_str("(* 1.5 2)").unwrap(),
+        Expr::Float(3.0),
+        "mixed arithmetic is done in floats"
+    );
+    assert_eq!(run("(* 1.5 2)"), "3.0");
+    assert_eq!(run("(/ 7.0 2)"), "3.5");
+}
+
+#[test]
+fn test_math_in_double_precision() {
+    assert_eq!(run("(sqrt 2)"), "1.4142135623730951");
+    assert_eq!(run("(log 10)"), "2.302585092994046");
+    assert_eq!(run("(** 2.0 0.5)"), "1.4142135623730951");
+    assert_eq!(run("(** 1.1 2)"), "1.2100000000000002");
+    assert_eq!(run("(truncate 12345678901.9)"), "12345678901");
+    assert_eq!(run("(truncate -3.5)"), "-3");
+}
+
+#[test]
+fn test_format_float() {
+    assert_eq!(run("(format-float 3.14159 2)"), "\"3.14\"");
+    assert_eq!(run("(format-float 2 3)"), "\"2.000\"");
+    assert_eq!(run("(format-float (/ 1.0 3) 0)"), "\"0\"");
+    assert_eq!(run("(format-float -0.5 1)"), "\"-0.5\"");
+    assert!(eval_str("(format-float 1.0 -1)").is_err());
+    assert!(eval_str("(format-float \"1.0\" 2)").is_err());
+}
+
+#[test]
+fn test_float_comparison() {
+    assert_eq!(run("(< 0.1 0.2)"), "true");
+    assert_eq!(run("(>= 2 1.5)"), "true");
+    assert_eq!(run("(< 9007199254740993 9007199254740992.0)"), "false");
+    assert!(eval_str("(< 1 \"2\")").is_err());
+}
@@ -0,0 +1,91 @@
+mod common;
+use common::eval_str;
+
+fn formatted(input: &str) -> String {
+    match eval_str(input).unwrap() {
+        jlisp::ast::Expr::String(s) => s,
+        e => panic!("expected a string, got {e}"),
+    }
+}
+
+#[test]
+fn test_placeholders() {
+    assert_eq!(formatted(r#"(format "x={} y={}" 1 2.5)"#), "x=1 y=2.5");
+    assert_eq!(formatted(r#"(format "{1}-{0}-{1}" "a" "b")"#), "b-a-b");
+    assert_eq!(
+        formatted(r#"(format "{{literal}} {}" [1 "two"])"#),
+        "{literal} [1 \"two\"]"
+    );
+    assert_eq!(formatted(r#"(format "{} {}" "plain" 'c')"#), "plain c");
+    assert_eq!(formatted(r#"(format "{:?}" "quoted\n")"#), "\"quoted\\n\"");
+    assert_eq!(
+        formatted(r#"(format "no placeholders")"#),
+        "no placeholders"
+    );
+}
+
+#[test]
+fn test_width_and_alignment() {
+    assert_eq!(formatted(r#"(format "[{:5}]" 42)"#), "[   42]");
+    assert_eq!(formatted(r#"(format "[{:5}]" "ab")"#), "[ab   ]");
+    assert_eq!(formatted(r#"(format "[{:>5}]" "ab")"#), "[   ab]");
+    assert_eq!(formatted(r#"(format "[{:^6}]" "ab")"#), "[  ab  ]");
+    assert_eq!(formatted(r#"(format "[{:*<5}]" 7)"#), "[7****]");
+    assert_eq!(formatted(r#"(format "[{:05}]" -42)"#), "[-0042]");
+    assert_eq!(formatted(r#"(format "[{:+}]" 3)"#), "[+3]");
+}
+
+#[test]
+fn test_precision() {
+    assert_eq!(formatted(r#"(format "{:.2}" 3.14159)"#), "3.14");
+    assert_eq!(formatted(r#"(format "{:8.3}" 2.0)"#), "   2.000");
+    assert_eq!(formatted(r#"(format "{:.3}" "truncated")"#), "tru");
+    assert_eq!(formatted(r#"(format "{:.2e}" 1234.5)"#), "1.23e3");
+}
+
+#[test]
+fn test_radix() {
+    assert_eq!(
+        formatted(r#"(format "{:x} {:X} {:#x}" 255 255 255)"#),
+        "ff FF 0xff"
+    );
+    assert_eq!(formatted(r#"(format "{:08b}" 5)"#), "00000101");
+    assert_eq!(formatted(r#"(format "{:#o}" 8)"#), "0o10");
+    assert_eq!(formatted(r#"(format "{:x}" -255)"#), "-ff");
+    assert_eq!(
+        formatted(r#"(format "{:x}" (** 2 64))"#),
+        "10000000000000000"
+    );
+    // zero padding goes between the prefix and the digits, as in Rust
+    assert_eq!(formatted(r#"(format "{:#010b}" -5)"#), "-0b0000101");
+    assert_eq!(formatted(r#"(format "{:#06x}" 255)"#), "0x00ff");
+    assert_eq!(formatted(r#"(format "{:+#x}" 255)"#), "+0xff");
+    assert_eq!(formatted(r#"(format "{:>#8x}" 255)"#), "    0xff");
+}
+
+#[test]
+fn test_format_errors() {
+    assert!(eval_str(r#"(format "{} {}" 1)"#).is_err());
+    assert!(eval_str(r#"(format "{}" 1 2)"#).is_err());
+    assert!(eval_str(r#"(format "{" 1)"#).is_err());
+    assert!(eval_str(r#"(format "}" 1)"#).is_err());
+    assert!(eval_str(r#"(format "{:q}" 1)"#).is_err());
+    assert!(eval_str(r#"(format "{:x}" 1.5)"#).is_err());
+    // widths and precisions are capped instead of allocating without bound
+    assert!(eval_str(r#"(format "{:9999999999999}" 1)"#).is_err());
+    assert!(eval_str(r#"(format "{:99999999999999999999999}" 1)"#).is_err());
+    assert!(eval_str(r#"(format "{:.70000}" 1.5)"#).is_err());
+    assert_eq!(formatted(r#"(format "{:65535}" 1)"#).len(), 65535);
+}
+
+#[test]
+fn test_str_and_repr() {
+    assert_eq!(
+        formatted("(str 1 \" \" 2.5 'c' [1 \"a\"])"),
+        "1 2.5c[1 \"a\"]"
+    );
+    assert_eq!(formatted("(str nil true 1/2)"), "niltrue1/2");
+    assert_eq!(formatted("(repr \"a\\\"b\")"), "\"a\\\"b\"");
+    assert_eq!(formatted("(repr 'c')"), "'c'");
+    assert_eq!(formatted("(repr {1 \"x\"})"), "{1 \"x\"}");
+}
@@ -0,0 +1,101 @@
+use jlisp::ast::Expr;
+mod common;
+use common::{eval_in, eval_str};
+use jlisp::interpreter::Interpreter;
+
+fn gc_stats(interp: &Interpreter) -> (i64, i64) {
+    let Expr::List(stats) = eval_in(interp, "(gc-stats)").unwrap() else {
+        panic!("gc-stats should return a list");
+    };
+    match stats[..] {
+        [Expr::Number(live), Expr::Number(freed)] => (live, freed),
+        _ => panic!("unexpected gc-stats {:?}", stats),
+    }
+}
+
+#[test]
+fn test_gc_frees_cycles() {
+    let interp = Interpreter::new();
+    // every call leaves its env and the closure's env pointing at each other
+    eval_in(&interp, "(fun [mk x] [do (= [g] (\\ [y] [+ x y])) (g 1)])").unwrap();
+    for _ in 0..100 {
+        assert_eq!(eval_in(&interp, "(mk 1)").unwrap(), Expr::Number(2));
+    }
+
+    let (live_before, freed_before) = gc_stats(&interp);
+    let Expr::Number(freed) = eval_in(&interp, "(gc)").unwrap() else {
+        panic!("gc should return a number");
+    };
+    assert!(freed >= 200, "only freed {freed} envs");
+
+    let (live_after, freed_after) = gc_stats(&interp);
+    assert!(live_after <= live_before - 200);
+    assert!(freed_after >= freed_before + 200);
+}
+
+#[test]
+fn test_gc_keeps_closures() {
+    let interp = Interpreter::new();
+    eval_in(&interp, "(def [adder] ((\\ [x] [\\ [y] [+ x y]]) 10))").unwrap();
+    eval_in(&interp, "(gc)").unwrap();
+    let res = eval_in(&interp, "(adder 5)").unwrap();
+    assert_eq!(res, Expr::Number(15));
+}
+
+#[test]
+fn test_gc_keeps_values_in_flight() {
+    // the closure is only held by the argument list while gc runs
+    let res = eval_str("((\\ [f n] [f 5]) ((\\ [x] [\\ [y] [+ x y]]) 1) (gc))").unwrap();
+    assert_eq!(res, Expr::Number(6));
+}
+
+#[test]
+fn test_gc_keeps_lists_of_closures() {
+    let interp = Interpreter::new();
+    eval_in(&interp, "(def [fs] (list ((\\ [x] [\\ [y] [* x y]]) 3)))").unwrap();
+    eval_in(&interp, "(gc)").unwrap();
+    let res = eval_in(&interp, "((eval (head fs)) 4)").unwrap();
+    assert_eq!(res, Expr::Number(12));
+}
+
+#[test]
+fn test_gc_shared_lambda_bodies() {
+    let interp = Interpreter::new();
+    // partial applications share their body with the lambda they came from
+    eval_in(&interp, "(def [add] (\\ [a b] [+ a b]))").unwrap();
+    eval_in(&interp, "(def [inc] (add 1))").unwrap();
+    eval_in(&interp, "(def [fs] (list inc inc add))").unwrap();
+    eval_in(&interp, "(gc)").unwrap();
+    assert_eq!(eval_in(&interp, "(inc 2)").unwrap(), Expr::Number(3));
+}
+
+#[test]
+fn test_gc_maps() {
+    let interp = Interpreter::new();
+    // a closure stored in a map in the env it captured
+    eval_in(
+        &interp,
+        "(fun [mk x] [do (= [m] (assoc {} 0 (\\ [y] [+ x y]))) ((get m 0) 1)])",
+    )
+    .unwrap();
+    for _ in 0..50 {
+        assert_eq!(eval_in(&interp, "(mk 1)").unwrap(), Expr::Number(2));
+    }
+    let Expr::Number(freed) = eval_in(&interp, "(gc)").unwrap() else {
+        panic!("gc should return a number");
+    };
+    assert!(freed >= 100, "only freed {freed} envs");
+
+    // closures only held by a map stay alive
+    eval_in(&interp, "(def [fm] {})").unwrap();
+    eval_in(
+        &interp,
+        "(def [fm] (assoc fm 1 ((\\ [x] [\\ [y] [* x y]]) 3)))",
+    )
+    .unwrap();
+    eval_in(&interp, "(gc)").unwrap();
+    assert_eq!(
+        eval_in(&interp, "((get fm 1) 4)").unwrap(),
+        Expr::Number(12)
+    );
+}
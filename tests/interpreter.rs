@@ -0,0 +1,119 @@
+use jlisp::ast::{Error, Expr};
+use jlisp::builtin::Arity;
+use jlisp::interpreter::Interpreter;
+use jlisp::vm::Evaluator;
+
+#[test]
+fn test_eval_str_keeps_definitions() {
+    let interp = Interpreter::new();
+    interp
+        .eval_str("(def [x] 40) (fun [add-x n] [+ x n])")
+        .unwrap();
+    assert_eq!(interp.eval_str("(add-x 2)").unwrap().to_string(), "42");
+    assert_eq!(interp.get_global("x").unwrap().to_string(), "40");
+    assert!(interp.get_global("y").is_none());
+}
+
+#[test]
+fn test_interpreters_are_independent() {
+    let a = Interpreter::new();
+    let b = Interpreter::new();
+    a.eval_str("(def [x] 1)").unwrap();
+    b.eval_str("(def [x] 2)").unwrap();
+    assert_eq!(a.get_global("x").unwrap().to_string(), "1");
+    assert_eq!(b.get_global("x").unwrap().to_string(), "2");
+    a.register("secret", Arity::Exact(0), &[], |_, _| Ok(Expr::Number(7)));
+    assert_eq!(a.eval_str("(secret)").unwrap().to_string(), "7");
+    assert!(b.eval_str("(secret)").is_err());
+}
+
+#[test]
+fn test_call() {
+    let interp = Interpreter::new();
+    interp.eval_str("(fun [pair a b] [list a b])").unwrap();
+    let pair: Expr = interp
+        .call("pair", vec![Expr::Number(1), Expr::String("b".into())])
+        .unwrap();
+    assert_eq!(pair.to_string(), "[1 \"b\"]");
+    // arguments are values, a list isn't run as code
+    let head: Expr = interp
+        .call(
+            "head",
+            vec![Expr::List(vec![Expr::Symbol("undefined".into())].into())],
+        )
+        .unwrap();
+    assert_eq!(head.to_string(), "[undefined]");
+    assert!(matches!(
+        interp.call::<_, Expr>("nope", ()),
+        Err(Error::UndefinedSymbol { .. })
+    ));
+}
+
+#[test]
+fn test_globals_and_settings() {
+    let interp = Interpreter::bare().with_evaluator(Evaluator::TreeWalk);
+    interp.set_global("limit", Expr::Number(3));
+    assert_eq!(interp.eval_str("(* limit 2)").unwrap().to_string(), "6");
+    assert!(interp.eval_str("(map head [[1]])").is_err());
+    // the evaluator is a setting of the interpreter
+    assert_eq!(interp.evaluator(), Evaluator::TreeWalk);
+    assert_eq!(Interpreter::bare().evaluator(), Evaluator::Vm);
+}
+
+#[test]
+fn test_envs_are_collected_per_interpreter() {
+    let a = Interpreter::new();
+    let b = Interpreter::new();
+    let live = |interp: &Interpreter| interp.eval_str("(head (gc-stats))").unwrap();
+    let before = live(&b);
+    // every call leaves its env and the closure's env pointing at each other
+    a.eval_str("(fun [mk x] [do (= [g] (\\ [y] [+ x y])) (g 1)])")
+        .unwrap();
+    a.eval_str("(map mk (range 100))").unwrap();
+    assert_eq!(live(&b), before);
+    assert!(a.eval_str("(gc)").unwrap() != Expr::Number(0));
+    assert_eq!(b.eval_str("(gc)").unwrap(), Expr::Number(0));
+}
+
+#[test]
+fn test_eval_errors() {
+    let interp = Interpreter::new();
+    let err = interp
+        .eval_source("script.jl", "(def [a] 1)\n(+ a b)")
+        .unwrap_err();
+    assert_eq!(err.to_string(), "undefined symbol 'b' at script.jl:2:1");
+    assert!(matches!(
+        interp.eval_str("(+ 1"),
+        Err(Error::ParseError { .. })
+    ));
+    assert!(matches!(
+        interp.eval_file("no/such/file.jl"),
+        Err(Error::IoError { .. })
+    ));
+}
+
+#[test]
+fn test_eval_file() {
+    let interp = Interpreter::new();
+    interp.eval_file("tests/fixtures/spans.jl").unwrap();
+    assert!(interp.get_global("half").is_some());
+}
+
+#[test]
+fn test_interpreters_move_between_threads() {
+    let interp = Interpreter::new();
+    interp.eval_str("(fun [sq n] [* n n])").unwrap();
+    let interp = std::thread::spawn(move || {
+        assert_eq!(interp.eval_str("(sq 7)").unwrap(), Expr::Number(49));
+        interp
+            .eval_str("(def [from-thread] (map sq [1 2]))")
+            .unwrap();
+        interp
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        interp.get_global("from-thread").unwrap().to_string(),
+        "[1 4]"
+    );
+}
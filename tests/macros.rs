@@ -0,0 +1,98 @@
+mod common;
+use common::{eval_in, run_in};
+use jlisp::ast::Expr;
+use jlisp::interpreter::Interpreter;
+use jlisp::vm::Evaluator;
+
+fn both(f: impl Fn(&Interpreter)) {
+    for evaluator in [Evaluator::Vm, Evaluator::TreeWalk] {
+        f(&Interpreter::new().with_evaluator(evaluator));
+    }
+}
+
+#[test]
+fn test_quasiquote() {
+    both(|interp| {
+        run_in(interp, "(def [x] 5)");
+        assert_eq!(run_in(interp, "`(a ,x b)"), "(a 5 b)");
+        assert_eq!(
+            run_in(interp, "`[a ,(+ x 1) ,@[1 2] [c ,x]]"),
+            "[a 6 1 2 [c 5]]"
+        );
+        assert_eq!(run_in(interp, "`(f ,@(list 1 2) ,@[])"), "(f 1 2)");
+        // only the outermost quasiquote is filled in
+        assert_eq!(
+            run_in(interp, "`(a `(b ,(c ,x)))"),
+            "(a (quasiquote (b (unquote (c 5)))))"
+        );
+    });
+}
+
+#[test]
+fn test_defmacro_args_unevaluated() {
+    both(|interp| {
+        run_in(interp, "(defmacro [quote-it x] `(head [,x]))");
+        assert_eq!(
+            run_in(interp, "(quote-it (undefined 1 2))"),
+            "[(undefined 1 2)]"
+        );
+        assert_eq!(
+            run_in(interp, "(macroexpand [quote-it (+ 1 2)])"),
+            "(head [(+ 1 2)])"
+        );
+    });
+}
+
+#[test]
+fn test_macro_expands_in_caller_env() {
+    both(|interp| {
+        run_in(interp, "(defmacro [swap-args f a b] `(,f ,b ,a))");
+        run_in(interp, "(fun [go y] [swap-args - y 10])");
+        assert_eq!(eval_in(interp, "(go 3)").unwrap(), Expr::Number(7));
+    });
+}
+
+#[test]
+fn test_when_unless() {
+    both(|interp| {
+        assert_eq!(run_in(interp, "(when (> 2 1) (= [y] 1) (+ y 1))"), "2");
+        assert_eq!(run_in(interp, "(when (< 2 1) (undefined))"), "nil");
+        assert_eq!(run_in(interp, "(unless (< 2 1) 3)"), "3");
+        assert_eq!(
+            run_in(interp, "(macroexpand [unless c (print 1)])"),
+            "(if c [nil] [do (print 1)])"
+        );
+    });
+}
+
+#[test]
+fn test_thread_first() {
+    both(|interp| {
+        assert_eq!(run_in(interp, "(-> 5 (+ 1) (* 2) (- 3))"), "9");
+        assert_eq!(run_in(interp, "(-> [1 2 3] (tail) (len))"), "2");
+        // only the outermost call is expanded
+        assert_eq!(
+            run_in(interp, "(macroexpand [-> x (f a) (g b)])"),
+            "(g (->step x (f a)) b)"
+        );
+        assert_eq!(run_in(interp, "(macroexpand [->step x (f a)])"), "(f x a)");
+    });
+}
+
+#[test]
+fn test_macro_tail_recursion() {
+    // the expansion runs in tail position
+    both(|interp| {
+        run_in(interp, "(fun [count n] [unless (== n 0) (count (- n 1))])");
+        assert_eq!(run_in(interp, "(count 100000)"), "nil");
+    });
+}
+
+#[test]
+fn test_macro_error_traced() {
+    both(|interp| {
+        run_in(interp, "(defmacro [bad x] [head x])");
+        let err = eval_in(interp, "(bad 1)").unwrap_err().to_string();
+        assert!(err.contains("in 'bad' at <test>:1:1"), "{err}");
+    });
+}
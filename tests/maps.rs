@@ -0,0 +1,98 @@
+mod common;
+use common::{eval_in, eval_str, run};
+use jlisp::ast::Expr;
+use jlisp::interpreter::Interpreter;
+
+#[test]
+fn test_map_literal() {
+    assert_eq!(run("{}"), "{}");
+    assert_eq!(
+        run("{1 \"one\" 'a' [x y] sym 2.5}"),
+        "{1 \"one\" 'a' [x y] sym 2.5}"
+    );
+    // later keys win
+    assert_eq!(run("{1 2 1 3}"), "{1 3}");
+    assert!(eval_str("{1 2 3}").is_err());
+}
+
+#[test]
+fn test_map_literal_is_not_evaluated() {
+    assert_eq!(run("{1 (+ 1 2)}"), "{1 (+ 1 2)}");
+    assert_eq!(run("(keys {x 1})"), "[x]");
+    // values that have to be computed go in with assoc
+    assert_eq!(run("(assoc {} 1 (+ 1 2))"), "{1 3}");
+}
+
+#[test]
+fn test_get_and_has() {
+    assert_eq!(run("(get {a 1 [1 2] 2} [1 2])"), "2");
+    assert_eq!(run("(get {\"a\" 1} \"b\")"), "nil");
+    assert_eq!(run("(get {\"a\" 1} \"b\" 0)"), "0");
+    assert_eq!(run("(has? {\"k\" nil} \"k\")"), "true");
+    assert_eq!(run("(has? {\"k\" nil} 'k')"), "false");
+    assert!(eval_str("(get [1 2] 0)").is_err());
+}
+
+#[test]
+fn test_assoc_dissoc() {
+    let interp = Interpreter::new();
+    assert_eq!(run("(assoc {} 1 2 3 4)"), "{1 2 3 4}");
+    assert_eq!(run("(assoc {1 2} 1 5)"), "{1 5}");
+    assert_eq!(run("(dissoc {1 2 3 4 5 6} 3 7)"), "{1 2 5 6}");
+    assert!(eval_str("(assoc {} 1)").is_err());
+
+    // maps are values, assoc doesn't change the original
+    eval_in(&interp, "(def [m] {\"a\" 1})").unwrap();
+    eval_in(&interp, "(def [n] (assoc m \"b\" 2))").unwrap();
+    assert_eq!(eval_in(&interp, "m").unwrap().to_string(), "{\"a\" 1}");
+    assert_eq!(
+        eval_in(&interp, "n").unwrap().to_string(),
+        "{\"a\" 1 \"b\" 2}"
+    );
+}
+
+#[test]
+fn test_keys_vals_merge() {
+    assert_eq!(run("(keys {c 1 a 2 b 3})"), "[c a b]");
+    assert_eq!(run("(vals {c 1 a 2 b 3})"), "[1 2 3]");
+    assert_eq!(run("(merge {a 1 b 2} {b 3} {c 4})"), "{a 1 b 3 c 4}");
+    assert_eq!(run("(merge {})"), "{}");
+    assert_eq!(run("(len {a 1 b 2})"), "2");
+}
+
+#[test]
+fn test_update() {
+    assert_eq!(run("(update {1 1} 1 (\\ [x] [+ x 1]))"), "{1 2}");
+    assert_eq!(run("(update {} 1 (\\ [x] [if x [x] [0]]))"), "{1 0}");
+    assert_eq!(run("(update {1 -3} 1 abs)"), "{1 3}");
+}
+
+#[test]
+fn test_map_equality_and_keys() {
+    // order doesn't matter for equality, or for maps used as keys
+    assert_eq!(run("(== {a 1 b 2} {b 2 a 1})"), "true");
+    assert_eq!(run("(== {a 1} {a 2})"), "false");
+    assert_eq!(run("(get {{a 1 b 2} yes} {b 2 a 1})"), "yes");
+    assert_eq!(run("(get {0.0 zero} -0.0)"), "zero");
+    assert_eq!(
+        eval_str("(if {} [1] [2])").unwrap(),
+        Expr::Number(2),
+        "empty maps are falsy"
+    );
+}
+
+#[test]
+fn test_map_counter() {
+    let interp = Interpreter::new();
+    eval_in(
+        &interp,
+        "(fun [count-chars s] [foldl (\\ [m c] [update m c (\\ [n] [if n [+ n 1] [1]])]) {} (chars s)])",
+    )
+    .unwrap();
+    assert_eq!(
+        eval_in(&interp, "(count-chars \"abca\")")
+            .unwrap()
+            .to_string(),
+        "{'a' 2 'b' 1 'c' 1}"
+    );
+}
@@ -0,0 +1,126 @@
+use jlisp::ast::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+mod common;
+use common::{eval_str, run};
+
+// the kind and message of the error `input` fails with
+fn err(input: &'static str) -> (&'static str, String) {
+    let err = eval_str(input).unwrap_err();
+    let err = err.downcast_ref::<Error>().unwrap();
+    (err.kind(), err.message())
+}
+
+#[test]
+fn test_qualified_access() {
+    assert_eq!(
+        run("(do (import tests/fixtures/modules/geometry) (geometry/area 2))"),
+        "12.0"
+    );
+    assert_eq!(
+        run("(do (import tests/fixtures/modules/geometry) geometry/pi)"),
+        "3.0"
+    );
+    // the module's own imports and definitions stay in its namespace
+    assert_eq!(
+        err("(do (import tests/fixtures/modules/geometry) square)").0,
+        "undefined-symbol"
+    );
+    assert_eq!(
+        err("(do (import tests/fixtures/modules/geometry) pi)").0,
+        "undefined-symbol"
+    );
+    assert_eq!(
+        err("(do (import tests/fixtures/modules/geometry) geometry/volume)").0,
+        "undefined-symbol"
+    );
+}
+
+#[test]
+fn test_alias_and_selective_import() {
+    assert_eq!(
+        run("(do (import tests/fixtures/modules/geometry as g) (g/area 1))"),
+        "3.0"
+    );
+    assert_eq!(
+        run("(do (import tests/fixtures/modules/geometry [area]) (area 1))"),
+        "3.0"
+    );
+    assert_eq!(
+        run("(do (import \"tests/fixtures/modules/geometry.jl\" [pi] as g) (list pi (g/area 1)))"),
+        "[3.0 3.0]"
+    );
+    assert_eq!(
+        err("(do (import tests/fixtures/modules/geometry [volume]) 1)").0,
+        "undefined-symbol"
+    );
+}
+
+#[test]
+fn test_namespace_does_not_shadow() {
+    // `str` is still the builtin after importing the `str` module
+    assert_eq!(run("(do (import str) (str 1 2))"), "\"12\"");
+}
+
+#[test]
+fn test_stdlib() {
+    assert_eq!(
+        run("(do (import str) (str/capitalize \"hello\"))"),
+        "\"Hello\""
+    );
+    assert_eq!(run("(do (import str) (str/blank? \"  \"))"), "true");
+    assert_eq!(
+        run("(do (import str [join-with]) (join-with \", \" [1 2 3]))"),
+        "\"1, 2, 3\""
+    );
+}
+
+#[test]
+fn test_stdlib_is_built_in() {
+    // a script run outside the source tree still finds the stdlib
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("stdlib");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("main.jl"),
+        "(import str)\n(print (str/join-with \"-\" [\"a\" 1 2.5]))\n",
+    )
+    .unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_jlisp"))
+        .arg("main.jl")
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&out.stdout), "\"a-1-2.5\"\n");
+}
+
+#[test]
+fn test_shared_imports() {
+    assert_eq!(
+        run(
+            "(do (import tests/fixtures/modules/geometry) (import tests/fixtures/modules/shapes) (list (shapes/box 3) (geometry/area 1)))"
+        ),
+        "[9 3.0]"
+    );
+}
+
+#[test]
+fn test_import_errors() {
+    let (kind, msg) = err("(import tests/fixtures/modules/ping)");
+    assert_eq!(kind, "import");
+    assert!(msg.contains("circular import"), "{msg}");
+    assert!(
+        msg.contains("ping.jl -> ") && msg.ends_with("ping.jl"),
+        "{msg}"
+    );
+
+    assert_eq!(err("(import no/such/module)").0, "io");
+    assert_eq!(
+        err("(import tests/fixtures/modules/broken)").0,
+        "undefined-symbol"
+    );
+    // a module that failed isn't cached, importing it again fails again
+    assert!(eval_str("(do (try (import tests/fixtures/modules/broken) (catch e 0)) (import tests/fixtures/modules/broken))").is_err());
+    assert!(eval_str("(import 1)").is_err());
+    assert!(eval_str("(import str with s)").is_err());
+}
@@ -0,0 +1,178 @@
+use jlisp::ast::{Error, Expr};
+use jlisp::builtin::{Arity, Type, register, setup_builtins};
+use jlisp::env::Env;
+use jlisp::interpreter::Interpreter;
+use jlisp::span::{Source, Span};
+use jlisp::vm::Evaluator;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+fn eval(env: &Env, input: &str) -> Result<Expr, Error> {
+    let pe = jlisp::grammar::ExprParser::new();
+    let source = Source::new("<native>", input);
+    let expr = pe.parse(&source, input).unwrap();
+    expr.eval(env, Span::file(source.file()))
+}
+
+fn both(f: impl Fn(Evaluator)) {
+    for evaluator in [Evaluator::Vm, Evaluator::TreeWalk] {
+        f(evaluator);
+    }
+}
+
+fn host(evaluator: Evaluator) -> Interpreter {
+    let interp = Interpreter::new().with_evaluator(evaluator);
+    let env = interp.env();
+    register(
+        env,
+        "add-two",
+        Arity::Exact(2),
+        &[Type::Number, Type::Number],
+        |args, span| {
+            let a = args[0].clone().into_number("add-two", span)?;
+            let b = args[1].clone().into_number("add-two", span)?;
+            Ok(Expr::Number(a + b))
+        },
+    );
+    register(
+        env,
+        "shout",
+        Arity::AtLeast(2),
+        &[Type::String],
+        |args, span| {
+            let words = args
+                .into_iter()
+                .map(|w| w.into_string("shout", span).map(|w| w.to_uppercase()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Expr::String(words.join(" ")))
+        },
+    );
+    register(env, "first-of", Arity::Any, &[Type::Any], |args, _| {
+        Ok(args.into_iter().next().unwrap_or(Expr::Nil))
+    });
+    register(
+        env,
+        "name-of",
+        Arity::Exact(1),
+        &[Type::Symbol],
+        |args, span| {
+            let name = args[0].clone().into_symbol("name-of", span)?;
+            Ok(Expr::String(name))
+        },
+    );
+    interp
+}
+
+#[test]
+fn test_native_call() {
+    both(|evaluator| {
+        let interp = host(evaluator);
+        let env = interp.env();
+        assert_eq!(eval(env, "(add-two 40 2)").unwrap().to_string(), "42");
+        assert_eq!(
+            eval(env, "(map (\\ [x] [add-two x 1]) [1 2])")
+                .unwrap()
+                .to_string(),
+            "[2 3]"
+        );
+        assert_eq!(
+            eval(env, "(foldl add-two 0 [1 2 3])").unwrap().to_string(),
+            "6"
+        );
+        assert_eq!(eval(env, "(first-of 1 \"a\")").unwrap().to_string(), "1");
+        assert_eq!(eval(env, "add-two").unwrap().to_string(), "<builtin>");
+    });
+}
+
+#[test]
+fn test_native_checks() {
+    both(|evaluator| {
+        let interp = host(evaluator);
+        let env = interp.env();
+        let err = eval(env, "(add-two 1)").unwrap_err();
+        assert!(
+            matches!(
+                err.root(),
+                Error::WrongAmountOfArgs {
+                    expected: 2,
+                    received: 1,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+        let err = eval(env, "(add-two 1 \"x\")").unwrap_err();
+        assert!(
+            matches!(err.root(), Error::IncompatibleType { received, .. } if received == "String"),
+            "{err}"
+        );
+        // a Number has to fit in the i64 `into_number` gives back
+        let err = eval(env, "(add-two 1 (** 2 70))").unwrap_err();
+        assert!(
+            matches!(err.root(), Error::IncompatibleType { received, .. } if received == "big Number"),
+            "{err}"
+        );
+        assert_eq!(
+            eval(env, "(name-of (last [x]))").unwrap().to_string(),
+            "\"x\""
+        );
+        assert!(eval(env, "(name-of \"x\")").is_err());
+        assert!(eval(env, "(shout \"a\")").is_err());
+        // the last type covers the rest of a variadic call
+        assert!(eval(env, "(shout \"a\" \"b\" 3)").is_err());
+        assert_eq!(
+            eval(env, "(shout \"a\" \"b\")").unwrap().to_string(),
+            "\"A B\""
+        );
+    });
+}
+
+#[test]
+fn test_native_state_and_nullary() {
+    both(|evaluator| {
+        let interp = host(evaluator);
+        let env = interp.env();
+        let ticks = Arc::new(AtomicI64::new(0));
+        let counter = ticks.clone();
+        register(env, "tick", Arity::Exact(0), &[], move |_, _| {
+            Ok(Expr::Number(counter.fetch_add(1, Ordering::Relaxed) + 1))
+        });
+        // a lone `(tick)` calls it like `(gc)`
+        assert_eq!(eval(env, "(tick)").unwrap().to_string(), "1");
+        assert_eq!(eval(env, "(+ (tick) (tick))").unwrap().to_string(), "5");
+        assert_eq!(ticks.load(Ordering::Relaxed), 3);
+    });
+}
+
+#[test]
+fn test_builtins_are_handles() {
+    let env = setup_builtins();
+    let Some(Expr::Builtin(b)) = env.get("do") else {
+        panic!("do is a builtin");
+    };
+    assert_eq!(b.name(), "do");
+    assert!(b.is_special_form());
+    assert_eq!(b.arity(), Arity::AtLeast(0));
+    let Some(Expr::Builtin(b)) = env.get("get") else {
+        panic!("get is a builtin");
+    };
+    assert!(!b.is_special_form());
+    assert_eq!(b.arity(), Arity::AtLeast(2));
+    assert_eq!(b.types(), [Type::Map, Type::Any]);
+    // which is checked before the builtin runs
+    let err = eval(&env, "(keys {} {})").unwrap_err();
+    assert_eq!(err.kind(), "arity");
+    let err = eval(&env, "(sqrt \"4\")").unwrap_err();
+    assert_eq!(
+        err.message(),
+        "type error in 'sqrt' expected Number, Rational or Float, got String"
+    );
+    // builtins bound to other names still work and compare by what they are
+    assert_eq!(
+        eval(&env, "(do (def [plus] +) (plus 1 2))")
+            .unwrap()
+            .to_string(),
+        "3"
+    );
+    assert_eq!(eval(&env, "(== + +)").unwrap().to_string(), "true");
+}
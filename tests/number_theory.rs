@@ -0,0 +1,99 @@
+mod common;
+use common::{eval_str, run};
+
+#[test]
+fn test_quot_rem_mod() {
+    assert_eq!(run("(quot 7 2)"), "3");
+    assert_eq!(run("(quot -7 2)"), "-3");
+    assert_eq!(run("(rem -7 2)"), "-1");
+    assert_eq!(run("(mod -7 2)"), "1");
+    assert_eq!(run("(mod 7 -2)"), "-1");
+    assert_eq!(run("(mod (** 10 30) 7)"), "1");
+    assert!(eval_str("(mod 1 0)").is_err());
+    assert!(eval_str("(quot 1.5 1)").is_err());
+}
+
+#[test]
+fn test_gcd_lcm() {
+    assert_eq!(run("(gcd 12 18)"), "6");
+    assert_eq!(run("(gcd -12 18 27)"), "3");
+    assert_eq!(run("(gcd 0 5)"), "5");
+    assert_eq!(run("(lcm 4 6)"), "12");
+    // cycle lengths multiply past i64
+    assert_eq!(
+        run("(lcm 1000000007 998244353 1000000009)"),
+        "998244368971909710889394239"
+    );
+}
+
+#[test]
+fn test_modular_arithmetic() {
+    assert_eq!(run("(modpow 2 10 1000)"), "24");
+    assert_eq!(run("(modpow 3 200 1000000007)"), "136318165");
+    assert_eq!(run("(modinv 3 7)"), "5");
+    assert_eq!(run("(modinv -3 7)"), "2");
+    assert_eq!(run("(modpow 3 -1 7)"), "5");
+    assert!(eval_str("(modinv 2 4)").is_err());
+    assert!(eval_str("(modpow 2 3 0)").is_err());
+    // results are in [0, |m|) for a negative modulus too
+    assert_eq!(run("(modpow 2 10 -7)"), "2");
+    assert_eq!(run("(modpow -2 3 7)"), "6");
+    assert_eq!(run("(modinv 3 -7)"), "5");
+}
+
+#[test]
+fn test_domain_errors() {
+    for input in [
+        "(modinv 2 4)",
+        "(isqrt -1)",
+        "(factorize 0)",
+        "(** 0 -1)",
+        "(sqrt -1)",
+    ] {
+        let err = eval_str(input).unwrap_err();
+        assert!(
+            err.to_string().starts_with("domain error:"),
+            "{input}: {err}"
+        );
+    }
+    assert_eq!(
+        run(r#"(try (modinv 2 4) (catch e (get e "kind")))"#),
+        "\"domain\""
+    );
+}
+
+#[test]
+fn test_isqrt() {
+    assert_eq!(run("(isqrt 0)"), "0");
+    assert_eq!(run("(isqrt 99)"), "9");
+    assert_eq!(run("(isqrt (** 10 40))"), "100000000000000000000");
+    assert!(eval_str("(isqrt -1)").is_err());
+}
+
+#[test]
+fn test_primes() {
+    assert_eq!(run("(prime? 2)"), "true");
+    assert_eq!(run("(prime? 1)"), "false");
+    assert_eq!(run("(prime? 561)"), "false");
+    assert_eq!(run("(prime? 1000000007)"), "true");
+    assert_eq!(run("(prime? (- (** 2 89) 1))"), "true");
+    assert_eq!(run("(prime? (* 1000000007 1000000009))"), "false");
+    assert_eq!(run("(factorize 1)"), "[]");
+    assert_eq!(run("(factorize 360)"), "[2 2 2 3 3 5]");
+    assert_eq!(
+        run("(factorize (* 1000000007 1000000009 1000000007))"),
+        "[1000000007 1000000007 1000000009]"
+    );
+    assert!(eval_str("(factorize 0)").is_err());
+}
+
+#[test]
+fn test_crt() {
+    assert_eq!(run("(crt [2 3 2] [3 5 7])"), "23");
+    assert_eq!(run("(crt [0 -1] [7 13])"), "77");
+    // moduli don't have to be coprime
+    assert_eq!(run("(crt [1 3] [4 6])"), "9");
+    assert_eq!(run("(crt [1 2] [4 6])"), "nil");
+    assert_eq!(run("(crt [] [])"), "0");
+    assert!(eval_str("(crt [1 2] [3])").is_err());
+}
@@ -0,0 +1,45 @@
+use jlisp::ast::Error;
+use jlisp::span::{Source, Span};
+mod common;
+use common::eval_str;
+
+#[test]
+fn test_prelude_is_loaded() {
+    assert_eq!(
+        eval_str("(map (\\ [x] [* x x]) [1 2 3])")
+            .unwrap()
+            .to_string(),
+        "[1 4 9]"
+    );
+    assert_eq!(
+        eval_str("(foldl + 0 (filter (\\ [x] [> x 1]) [1 2 3]))")
+            .unwrap()
+            .to_string(),
+        "5"
+    );
+    assert_eq!(eval_str("(when true 1)").unwrap().to_string(), "1");
+}
+
+#[test]
+fn test_bare_env() {
+    let env = jlisp::builtin::setup_builtins_bare();
+    let pe = jlisp::grammar::ExprParser::new();
+    let source = Source::new("<bare>", "(map head [[1]])");
+    let expr = pe.parse(&source, "(map head [[1]])").unwrap();
+    let err = expr.eval(&env, Span::file(source.file())).unwrap_err();
+    assert!(matches!(err.root(), Error::UndefinedSymbol { sym, .. } if sym == "map"));
+    assert!(env.get("head").is_some());
+}
+
+#[test]
+fn test_prelude_calls_name_it() {
+    let err = eval_str("(case 3 [1 10] [2 20])").unwrap_err();
+    let err = err.downcast_ref::<Error>().unwrap();
+    let files: Vec<String> = err
+        .trace()
+        .unwrap()
+        .frames()
+        .map(|f| f.span.file.name())
+        .collect();
+    assert!(files.iter().any(|f| f == "base.jl"), "{err}");
+}
@@ -0,0 +1,85 @@
+mod common;
+use common::{eval_str, run};
+use jlisp::ast::Expr;
+
+#[test]
+fn test_division_is_exact() {
+    assert_eq!(run("(/ 1 3)"), "1/3");
+    assert_eq!(run("(/ 6 4)"), "3/2");
+    assert_eq!(run("(/ -6 4)"), "-3/2");
+    assert_eq!(run("(/ 6 -4)"), "-3/2");
+    assert_eq!(eval_str("(* (/ 1 3) 3)").unwrap(), Expr::Number(1));
+    assert_eq!(eval_str("(/ 8 4)").unwrap(), Expr::Number(2));
+    assert_eq!(run("(/ 1 2 3)"), "1/6");
+    assert!(eval_str("(/ 1 0)").is_err());
+    assert!(eval_str("(/ 1/2 0)").is_err());
+}
+
+#[test]
+fn test_rational_literals() {
+    assert_eq!(run("2/4"), "1/2");
+    assert_eq!(eval_str("4/2").unwrap(), Expr::Number(2));
+    assert_eq!(run("-1/3"), "-1/3");
+    assert_eq!(run("(== 1/3 (/ 2 6))"), "true");
+    assert!(eval_str("1/0").is_err());
+}
+
+#[test]
+fn test_rational_arithmetic() {
+    assert_eq!(run("(+ 1/2 1/3)"), "5/6");
+    assert_eq!(run("(- 1/2 1/2)"), "0");
+    assert_eq!(run("(+ 1/2 1)"), "3/2");
+    assert_eq!(run("(* 2/3 3/4)"), "1/2");
+    assert_eq!(run("(- 1/2)"), "-1/2");
+    assert_eq!(run("(** 2/3 3)"), "8/27");
+    // negative powers are powers of the inverse
+    assert_eq!(run("(** 2 -1)"), "1/2");
+    assert_eq!(run("(** -2 -3)"), "-1/8");
+    assert_eq!(run("(** 2/3 -2)"), "9/4");
+    assert_eq!(run("(** 1/2 -3)"), "8");
+    assert_eq!(run("(** 2.0 -1)"), "0.5");
+    assert_eq!(run("(+ 1/2 0.25)"), "0.75");
+    assert_eq!(run("(/ 1 (** 2 70))"), "1/1180591620717411303424");
+    assert_eq!(run("(% 1/2 1/3)"), "1/6");
+    assert_eq!(run("(% 7/2 2)"), "3/2");
+    assert_eq!(run("(% 7 3/2)"), "1");
+    assert_eq!(run("(% -7/2 2)"), "-3/2");
+    assert!(eval_str("(% 1/2 0)").is_err());
+    assert!(eval_str("(% 1/2 0.5)").is_err());
+}
+
+#[test]
+fn test_rational_comparison() {
+    assert_eq!(run("(< 1/3 1/2)"), "true");
+    assert_eq!(run("(> 1/3 0.3)"), "true");
+    assert_eq!(run("(<= 3/2 1)"), "false");
+    assert_eq!(run("(== 1/2 0.5)"), "true");
+    assert_eq!(run("(== 1 1.0)"), "true");
+    assert_eq!(run("(!= 1/3 0.3)"), "true");
+    assert_eq!(run("(== 1 \"1\")"), "false");
+    assert_eq!(run("(== [1 2] [1 2])"), "true");
+    assert_eq!(run("(sort [1/2 -1 0.25 1/3 2])"), "[-1 0.25 1/3 1/2 2]");
+    assert_eq!(run("(min 1/2 1/3)"), "1/3");
+    assert_eq!(run("(max 1/2 1)"), "1");
+    assert_eq!(run("(abs -2/3)"), "2/3");
+    assert_eq!(run("(truncate 7/2)"), "3");
+    assert_eq!(run("(truncate -7/2)"), "-3");
+    assert_eq!(run("(floor 7/2)"), "3");
+    assert_eq!(run("(floor -7/2)"), "-4");
+    assert_eq!(run("(ceil 7/2)"), "4");
+    assert_eq!(run("(round 5/2)"), "3");
+    assert_eq!(run("(floor 4)"), "4");
+    assert_eq!(run("(floor 3.5)"), "3.0");
+    assert_eq!(run("#{1/2 1/3 1}"), "#{1/3 1/2 1}");
+}
+
+#[test]
+fn test_rational_conversions() {
+    assert_eq!(run("(numerator 6/4)"), "3");
+    assert_eq!(run("(denominator 6/4)"), "2");
+    assert_eq!(run("(numerator 5)"), "5");
+    assert_eq!(run("(denominator 5)"), "1");
+    assert_eq!(run("(exact->inexact 1/4)"), "0.25");
+    assert_eq!(run("(exact->inexact 3)"), "3.0");
+    assert!(eval_str("(numerator 0.5)").is_err());
+}
@@ -0,0 +1,64 @@
+mod common;
+use common::{eval_in, eval_str, run};
+use jlisp::interpreter::Interpreter;
+
+#[test]
+fn test_match_and_find_all() {
+    assert_eq!(run(r#"(re-match r"\d+" "move 3 from 1 to 2")"#), "\"3\"");
+    assert_eq!(run(r#"(re-match r"\d+" "no numbers")"#), "nil");
+    assert_eq!(
+        run(r#"(re-find-all r"\d+" "move 13 from 1 to 2")"#),
+        "[\"13\" \"1\" \"2\"]"
+    );
+    assert_eq!(run(r#"(re-find-all "x" "abc")"#), "[]");
+}
+
+#[test]
+fn test_compiled_regex() {
+    let interp = Interpreter::new();
+    eval_in(
+        &interp,
+        r#"(def [moves] (regex r"move (\d+) from (\d+) to (\d+)"))"#,
+    )
+    .unwrap();
+    assert_eq!(
+        eval_in(&interp, r#"(re-captures moves "move 3 from 1 to 2")"#)
+            .unwrap()
+            .to_string(),
+        "[\"move 3 from 1 to 2\" \"3\" \"1\" \"2\"]"
+    );
+    assert_eq!(
+        eval_in(&interp, "moves").unwrap().to_string(),
+        r#"(regex "move (\\d+) from (\\d+) to (\\d+)")"#
+    );
+    assert_eq!(run(r#"(== (regex "a+") (regex "a+"))"#), "true");
+    assert!(eval_str(r#"(regex "(")"#).is_err());
+    assert!(eval_str(r#"(re-match 1 "a")"#).is_err());
+}
+
+#[test]
+fn test_captures() {
+    assert_eq!(
+        run(r#"(re-captures r"(?P<name>\w+)=(?P<value>\d+)?" "x= y=2")"#),
+        "{\"name\" \"x\" \"value\" nil}"
+    );
+    assert_eq!(
+        run(r#"(re-captures r"(\w)(\d)" "a1 b2" true)"#),
+        "[[\"a1\" \"a\" \"1\"] [\"b2\" \"b\" \"2\"]]"
+    );
+    assert_eq!(run(r#"(re-captures r"(\d)" "none")"#), "nil");
+    assert_eq!(
+        run(r#"(get (re-captures r"(?P<n>\d+)" "abc 42") "n")"#),
+        "\"42\""
+    );
+}
+
+#[test]
+fn test_replace_and_split() {
+    assert_eq!(
+        run(r#"(re-replace r"(\w+)@(\w+)" "me@home you@work" "$2:$1")"#),
+        "\"home:me work:you\""
+    );
+    assert_eq!(run(r#"(re-replace "a" "banana" "o")"#), "\"bonono\"");
+    assert_eq!(run(r#"(re-split r",\s*" "1, 2,3")"#), "[\"1\" \"2\" \"3\"]");
+}
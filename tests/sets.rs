@@ -0,0 +1,63 @@
+mod common;
+use common::{eval_in, eval_str, run};
+use jlisp::ast::Expr;
+use jlisp::interpreter::Interpreter;
+
+#[test]
+fn test_set_literal() {
+    assert_eq!(run("#{}"), "#{}");
+    assert_eq!(run("#{3 1 2 1}"), "#{1 2 3}");
+    // printed in the same order no matter how they were built
+    assert_eq!(
+        run("#{10 9 -1.5 \"b\" \"a\" [1]}"),
+        "#{-1.5 9 10 [1] \"a\" \"b\"}"
+    );
+    assert_eq!(run("(set [c b a b])"), "#{a b c}");
+}
+
+#[test]
+fn test_add_remove_member() {
+    assert_eq!(run("(set-add #{1} 2 3)"), "#{1 2 3}");
+    assert_eq!(run("(set-remove #{1 2 3} 2 4)"), "#{1 3}");
+    assert_eq!(run("(member? #{[1 2] \"x\"} [1 2])"), "true");
+    assert_eq!(run("(member? #{[1 2] \"x\"} [2 1])"), "false");
+    assert_eq!(run("(len #{1 2 2})"), "2");
+    assert!(eval_str("(member? [1 2] 1)").is_err());
+}
+
+#[test]
+fn test_set_algebra() {
+    assert_eq!(run("(union #{1 2} #{2 3} #{4})"), "#{1 2 3 4}");
+    assert_eq!(run("(intersection #{1 2 3} #{2 3 4} #{3 2})"), "#{2 3}");
+    assert_eq!(run("(difference #{1 2 3} #{2} #{3})"), "#{1}");
+    assert_eq!(run("(subset? #{1 2} #{2 3 1})"), "true");
+    assert_eq!(run("(subset? #{1 4} #{2 3 1})"), "false");
+    assert_eq!(run("(subset? #{} #{})"), "true");
+}
+
+#[test]
+fn test_set_equality() {
+    assert_eq!(run("(== #{1 2 3} (set-add #{3} 2 1))"), "true");
+    assert_eq!(run("(== #{1 2} #{1 2 3})"), "false");
+    assert_eq!(run("(get {#{1 2} yes} #{2 1})"), "yes");
+    assert_eq!(
+        eval_str("(if #{} [1] [2])").unwrap(),
+        Expr::Number(2),
+        "empty sets are falsy"
+    );
+}
+
+#[test]
+fn test_visited_set() {
+    let interp = Interpreter::new();
+    eval_in(
+        &interp,
+        "(fun [visit seen l] [if l [visit (set-add seen (eval (head l))) (tail l)] [seen]])",
+    )
+    .unwrap();
+    let res = eval_in(
+        &interp,
+        "(len (visit #{} (join (range 1000) (range 1000))))",
+    );
+    assert_eq!(res.unwrap(), Expr::Number(1000));
+}
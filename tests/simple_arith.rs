@@ -29,6 +29,8 @@ fn test_mul() {
 #[test]
 fn test_div() {
     let res = eval_str("(/ 1 2)").unwrap();
+    assert_eq!(res.to_string(), "1/2");
+    let res = eval_str("(/ 1.0 2)").unwrap();
     assert_eq!(res, Expr::Float(0.5));
 }
 
@@ -0,0 +1,57 @@
+use jlisp::ast::Error;
+use jlisp::interpreter::Interpreter;
+use jlisp::span::{Source, Span};
+use jlisp::vm::Evaluator;
+
+fn eval_err(evaluator: Evaluator, input: &str) -> Error {
+    let interp = Interpreter::new().with_evaluator(evaluator);
+    let pe = jlisp::grammar::ExprParser::new();
+    let source = Source::new("<spans>", input);
+    let expr = pe.parse(&source, input).unwrap();
+    expr.eval(interp.env(), Span::file(source.file()))
+        .unwrap_err()
+}
+
+fn assert_at(err: &Error, file: &str, line: u32, col: u32) {
+    let span = err.span();
+    assert_eq!(
+        (span.file.name().as_str(), span.line, span.col),
+        (file, line, col),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_error_span_top_level() {
+    for evaluator in [Evaluator::Vm, Evaluator::TreeWalk] {
+        let err = eval_err(evaluator, "(+ 1 (foo 2))");
+        assert!(matches!(err.root(), Error::UndefinedSymbol { .. }));
+        assert_at(&err, "<spans>", 1, 6);
+        assert_eq!(err.to_string(), "undefined symbol 'foo' at <spans>:1:6");
+    }
+}
+
+#[test]
+fn test_error_span_in_loaded_lambda() {
+    for evaluator in [Evaluator::Vm, Evaluator::TreeWalk] {
+        let err = eval_err(
+            evaluator,
+            "(do (load \"tests/fixtures/spans.jl\") (half 4))",
+        );
+        assert!(matches!(err.root(), Error::DivisionByZero { .. }));
+        assert_at(&err, "tests/fixtures/spans.jl", 3, 3);
+
+        let err = eval_err(
+            evaluator,
+            "(do (load \"tests/fixtures/spans.jl\") (broken 4))",
+        );
+        assert!(matches!(err.root(), Error::IncompatibleType { .. }));
+        assert_at(&err, "tests/fixtures/spans.jl", 5, 3);
+    }
+}
+
+#[test]
+fn test_error_span_multi_line() {
+    let err = eval_err(Evaluator::Vm, "(do\n  (+ 1 1)\n    (head []))");
+    assert_at(&err, "<spans>", 3, 5);
+}
@@ -0,0 +1,79 @@
+mod common;
+use common::{eval_str, run};
+use jlisp::ast::Expr;
+
+#[test]
+fn test_case_and_trim() {
+    assert_eq!(run(r#"(upper "Hello, World")"#), r#""HELLO, WORLD""#);
+    assert_eq!(run(r#"(lower "Hello")"#), r#""hello""#);
+    assert_eq!(run("(upper 'a')"), "'A'");
+    assert_eq!(run(r#"(trim "  x y \n")"#), r#""x y""#);
+    assert_eq!(run(r#"(trim-left "  x ")"#), r#""x ""#);
+    assert_eq!(run(r#"(trim-right "  x ")"#), r#""  x""#);
+}
+
+#[test]
+fn test_searching() {
+    assert_eq!(run(r#"(starts-with? "move 3" "move")"#), "true");
+    assert_eq!(run(r#"(ends-with? "file.txt" ".jl")"#), "false");
+    assert_eq!(run(r#"(ends-with? "abc" 'c')"#), "true");
+    assert_eq!(run(r#"(index-of "héllo" "llo")"#), "2");
+    assert_eq!(run(r#"(index-of "hello" 'z')"#), "nil");
+    assert_eq!(run(r#"(replace "a-b-c" "-" ", ")"#), r#""a, b, c""#);
+    assert!(eval_str(r#"(replace "abc" "" "x")"#).is_err());
+}
+
+#[test]
+fn test_building() {
+    assert_eq!(run(r#"(repeat "ab" 3)"#), r#""ababab""#);
+    assert_eq!(run("(repeat '-' 4)"), r#""----""#);
+    assert_eq!(run(r#"(reverse "abc")"#), r#""cba""#);
+    assert_eq!(run("(reverse [1 2 3])"), "[3 2 1]");
+    assert_eq!(run(r#"(pad-left "7" 3)"#), r#""  7""#);
+    assert_eq!(run(r#"(pad-left "7" 3 '0')"#), r#""007""#);
+    assert_eq!(run(r#"(pad-right "ab" 4 '.')"#), r#""ab..""#);
+    assert_eq!(run(r#"(pad-right "abcdef" 4)"#), r#""abcdef""#);
+    assert!(eval_str(r#"(repeat "a" -1)"#).is_err());
+}
+
+#[test]
+fn test_splitting() {
+    assert_eq!(
+        run("(lines \"one\ntwo\r\nthree\n\")"),
+        r#"["one" "two" "three"]"#
+    );
+    assert_eq!(
+        run(r#"(words "  move 3   from\t1 ")"#),
+        r#"["move" "3" "from" "1"]"#
+    );
+    assert_eq!(run(r#"(split ", " "a, b, c")"#), r#"["a" "b" "c"]"#);
+    assert_eq!(run(r#"(split ',' "a,b")"#), r#"["a" "b"]"#);
+    assert_eq!(run("(split \"\n\n\" \"1\n2\n\n3\")"), r#"["1\n2" "3"]"#);
+    assert!(eval_str(r#"(split "" "abc")"#).is_err());
+}
+
+#[test]
+fn test_chars_and_lists() {
+    assert_eq!(run(r#"(string->list "ab")"#), "['a' 'b']");
+    assert_eq!(run("(list->string ['a' 'b' \"cd\"])"), r#""abcd""#);
+    assert_eq!(
+        run(r#"(list->string (reverse (string->list "abc")))"#),
+        r#""cba""#
+    );
+    assert!(eval_str("(list->string [1])").is_err());
+}
+
+#[test]
+fn test_char_classes() {
+    assert_eq!(run("(digit? '7')"), "true");
+    assert_eq!(run("(digit? 'x')"), "false");
+    assert_eq!(run(r#"(digit? "123")"#), "true");
+    assert_eq!(run(r#"(digit? "")"#), "false");
+    assert_eq!(run("(alpha? 'é')"), "true");
+    assert_eq!(run("(whitespace? '\\t')"), "true");
+    assert_eq!(eval_str("(char->int 'A')").unwrap(), Expr::Number(65));
+    assert_eq!(run("(int->char 97)"), "'a'");
+    assert_eq!(run("(int->char (+ (char->int 'a') 2))"), "'c'");
+    assert!(eval_str("(int->char -1)").is_err());
+    assert!(eval_str("(int->char 55296)").is_err());
+}
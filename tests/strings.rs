@@ -0,0 +1,57 @@
+mod common;
+use common::eval_str;
+use jlisp::ast::Expr;
+
+fn string(input: &str) -> String {
+    match eval_str(input).unwrap() {
+        Expr::String(s) => s,
+        e => panic!("expected a string, got {e}"),
+    }
+}
+
+#[test]
+fn test_escapes() {
+    assert_eq!(string(r#""a\nb""#), "a\nb");
+    assert_eq!(string(r#""tab\there""#), "tab\there");
+    assert_eq!(string(r#""say \"hi\"""#), "say \"hi\"");
+    assert_eq!(string(r#""back\\slash""#), "back\\slash");
+    assert_eq!(string(r#""\u{48}\u{e9}\u{1F600}""#), "H\u{e9}\u{1F600}");
+    assert_eq!(string(r#""\r\0\'""#), "\r\0'");
+    assert!(eval_str(r#""\q""#).is_err());
+    assert!(eval_str(r#""\u{110000}""#).is_err());
+    assert!(eval_str(r#""\u41""#).is_err());
+}
+
+#[test]
+fn test_multi_line_strings() {
+    assert_eq!(string("\"one\ntwo\n\""), "one\ntwo\n");
+    assert_eq!(eval_str("(len \"a\nb\")").unwrap(), Expr::Number(3));
+}
+
+#[test]
+fn test_raw_strings() {
+    assert_eq!(string(r#"r"\d+\n""#), "\\d+\\n");
+    assert_eq!(string(r##"r#"a "quoted" \word"#"##), "a \"quoted\" \\word");
+    assert_eq!(string(r##"r#"ends in a quote""#"##), "ends in a quote\"");
+    assert_eq!(string("r\"line one\nline two\""), "line one\nline two");
+    assert_eq!(
+        eval_str(r##"(list r#"a"# r#"b"#)"##).unwrap().to_string(),
+        "[\"a\" \"b\"]"
+    );
+}
+
+#[test]
+fn test_display_reads_back() {
+    for input in [
+        r#""plain""#,
+        r#""a\nb\tc""#,
+        r#""quote \" and \\ backslash""#,
+        r#""bell \u{7}""#,
+        r##"r#"raw \n "inside""#"##,
+    ] {
+        let value = eval_str(input).unwrap();
+        let printed = value.to_string();
+        assert_eq!(eval_str(&printed).unwrap(), value, "{printed} reads back");
+    }
+    assert_eq!(eval_str(r#""a\nb""#).unwrap().to_string(), r#""a\nb""#);
+}
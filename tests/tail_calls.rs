@@ -0,0 +1,78 @@
+use jlisp::ast::Expr;
+mod common;
+use common::{eval_in, eval_str};
+use jlisp::interpreter::Interpreter;
+use jlisp::vm::Evaluator;
+
+#[test]
+fn test_tail_recursive_countdown() {
+    let interp = Interpreter::new();
+    eval_in(&interp, "(fun [count n] [if (== n 0) [0] [count (- n 1)]])").unwrap();
+    let res = eval_in(&interp, "(count 1000000)").unwrap();
+    assert_eq!(res, Expr::Number(0));
+}
+
+#[test]
+fn test_foldl_million_elements() {
+    let res = eval_str("(foldl (\\ [acc x] [+ acc 1]) 0 (range 1000000))").unwrap();
+    assert_eq!(res, Expr::Number(1000000));
+}
+
+#[test]
+fn test_map_filter_long_lists() {
+    for evaluator in [Evaluator::Vm, Evaluator::TreeWalk] {
+        let interp = Interpreter::new().with_evaluator(evaluator);
+        let res = eval_in(&interp, "(sum (map (\\ [x] [* x 2]) (range 50000)))").unwrap();
+        assert_eq!(res, Expr::Number(2499950000));
+        let res = eval_in(
+            &interp,
+            "(len (filter (\\ [x] [== (% x 3) 0]) (range 50000)))",
+        );
+        assert_eq!(res.unwrap(), Expr::Number(16667));
+        let res = eval_in(&interp, "(map (\\ [x] [+ x 1]) (range 3))").unwrap();
+        assert_eq!(res, eval_in(&interp, "[1 2 3]").unwrap());
+        let res = eval_in(&interp, "(filter (\\ [x] [> x 5]) (range 3))").unwrap();
+        assert_eq!(res, eval_in(&interp, "[]").unwrap());
+    }
+}
+
+#[test]
+fn test_foldl_sum() {
+    let res = eval_str("(foldl + 0 (range 1000))").unwrap();
+    assert_eq!(res, Expr::Number(499500));
+}
+
+#[test]
+fn test_do_tail_position() {
+    let interp = Interpreter::new();
+    eval_in(
+        &interp,
+        "(fun [loop n] [do (+ 1 1) (if (== n 0) [n] [loop (- n 1)])])",
+    )
+    .unwrap();
+    let res = eval_in(&interp, "(loop 1000000)").unwrap();
+    assert_eq!(res, Expr::Number(0));
+}
+
+#[test]
+fn test_do_returns_last() {
+    let res = eval_str("(do 1 2 3)").unwrap();
+    assert_eq!(res, Expr::Number(3));
+}
+
+#[test]
+fn test_partial_application() {
+    let interp = Interpreter::new();
+    eval_in(&interp, "(def [add] (\\ [a b] [+ a b]))").unwrap();
+    let res = eval_in(&interp, "((add 1) 2)").unwrap();
+    assert_eq!(res, Expr::Number(3));
+}
+
+#[test]
+fn test_variadic_rest() {
+    let res = eval_str("((\\ [a & xs] [xs]) 1 2 3)").unwrap();
+    assert_eq!(
+        res,
+        Expr::List(vec![Expr::Number(2), Expr::Number(3)].into())
+    );
+}
@@ -0,0 +1,103 @@
+use jlisp::ast::Error;
+use jlisp::interpreter::Interpreter;
+use jlisp::span::{Source, Span};
+use jlisp::vm::Evaluator;
+
+fn eval_err(evaluator: Evaluator, input: &str) -> Error {
+    let interp = Interpreter::new().with_evaluator(evaluator);
+    let env = interp.env();
+    let pe = jlisp::grammar::JLispParser::new();
+    let source = Source::new("<traces>", input);
+    let mut res = Ok(jlisp::ast::Expr::Sexpr(Default::default()));
+    for expr in pe.parse(&source, input).unwrap().exprs {
+        res = expr.eval(env, Span::file(source.file()));
+        if res.is_err() {
+            break;
+        }
+    }
+    res.unwrap_err()
+}
+
+// the traced calls as `name@line:col`, outermost first
+fn calls(err: &Error) -> Vec<String> {
+    err.trace()
+        .map(|trace| {
+            trace
+                .frames()
+                .map(|f| format!("{}@{}:{}", f.name, f.span.line, f.span.col))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn both(f: impl Fn(Evaluator)) {
+    for evaluator in [Evaluator::Vm, Evaluator::TreeWalk] {
+        f(evaluator);
+    }
+}
+
+#[test]
+fn test_trace_nested_calls() {
+    both(|evaluator| {
+        let err = eval_err(
+            evaluator,
+            "(fun [inner x] [head x])
+(fun [outer x] [+ 1 (inner x)])
+(outer 5)",
+        );
+        assert!(matches!(err.root(), Error::IncompatibleType { .. }));
+        assert_eq!(calls(&err), ["outer@3:1", "inner@2:21"]);
+    });
+}
+
+#[test]
+fn test_trace_no_calls() {
+    both(|evaluator| {
+        let err = eval_err(evaluator, "(+ 1 (head 2))");
+        assert!(err.trace().is_none());
+        assert!(matches!(err, Error::IncompatibleType { .. }));
+    });
+}
+
+#[test]
+fn test_trace_through_base() {
+    both(|evaluator| {
+        let err = eval_err(evaluator, "(foldl (\\ [a b] [head b]) 0 [1 2])");
+        let calls = calls(&err);
+        assert_eq!(calls[0], "foldl@1:1");
+        assert!(calls.last().unwrap().starts_with("f@"), "{calls:?}");
+    });
+}
+
+// the call that entered the loop is gone, only the last tail call is left
+#[test]
+fn test_trace_tail_calls_replace_frames() {
+    both(|evaluator| {
+        let err = eval_err(
+            evaluator,
+            "(fun [loop n] [if (== n 0) [head n] [loop (- n 1)]])
+(loop 1000)",
+        );
+        assert_eq!(calls(&err), ["loop@1:37"]);
+    });
+}
+
+#[test]
+fn test_trace_is_capped() {
+    both(|evaluator| {
+        let err = eval_err(
+            evaluator,
+            "(fun [deep n] [if (== n 0) [head n] [+ 1 (deep (- n 1))]])
+(deep 500)",
+        );
+        let trace = err.trace().unwrap();
+        assert_eq!(trace.frames().count(), 20);
+        assert_eq!(trace.omitted(), 481);
+
+        let text = err.to_string();
+        assert!(text.starts_with("type error in 'head'"), "{text}");
+        assert!(text.contains("traceback (most recent call last):"));
+        assert!(text.contains("  ... 481 more calls\n"));
+        assert_eq!(text.lines().count(), 23);
+    });
+}
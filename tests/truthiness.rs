@@ -0,0 +1,103 @@
+mod common;
+use common::{eval_in, eval_str};
+use jlisp::ast::Expr;
+use jlisp::interpreter::Interpreter;
+
+#[test]
+fn test_literals() {
+    assert_eq!(eval_str("true").unwrap(), Expr::Bool(true));
+    assert_eq!(eval_str("false").unwrap(), Expr::Bool(false));
+    assert_eq!(eval_str("nil").unwrap(), Expr::Nil);
+    assert_eq!(eval_str("[true nil]").unwrap().to_string(), "[true nil]");
+    // only the exact words are literals
+    assert!(eval_str("nilly").is_err());
+}
+
+#[test]
+fn test_predicates_return_bools() {
+    assert_eq!(eval_str("(== 1 1)").unwrap(), Expr::Bool(true));
+    assert_eq!(eval_str("(!= 1 1)").unwrap(), Expr::Bool(false));
+    assert_eq!(eval_str("(< 1 2)").unwrap(), Expr::Bool(true));
+    assert_eq!(eval_str("(>= 1 2)").unwrap(), Expr::Bool(false));
+    assert_eq!(eval_str("(and 1 [1])").unwrap(), Expr::Bool(true));
+    assert_eq!(eval_str("(or 0 nil)").unwrap(), Expr::Bool(false));
+    assert_eq!(eval_str("(not nil)").unwrap(), Expr::Bool(true));
+}
+
+#[test]
+fn test_nil_is_not_empty_list() {
+    assert_eq!(eval_str("(== nil nil)").unwrap(), Expr::Bool(true));
+    assert_eq!(eval_str("(== nil [])").unwrap(), Expr::Bool(false));
+    assert_eq!(eval_str("(== true 1)").unwrap(), Expr::Bool(false));
+}
+
+#[test]
+fn test_truthiness() {
+    let falsy = ["false", "nil", "0", "0.0", "\"\"", "[]", "()"];
+    let truthy = ["true", "1", "-1", "0.5", "\"a\"", "[0]", "(\\ [x] [x])"];
+
+    for v in falsy {
+        assert_eq!(
+            eval_str(&format!("(if {v} [1] [2])")).unwrap(),
+            Expr::Number(2),
+            "{v}"
+        );
+        assert_eq!(
+            eval_str(&format!("(not {v})")).unwrap(),
+            Expr::Bool(true),
+            "{v}"
+        );
+    }
+    for v in truthy {
+        assert_eq!(
+            eval_str(&format!("(if {v} [1] [2])")).unwrap(),
+            Expr::Number(1),
+            "{v}"
+        );
+        assert_eq!(
+            eval_str(&format!("(and {v} {v})")).unwrap(),
+            Expr::Bool(true),
+            "{v}"
+        );
+        assert_eq!(
+            eval_str(&format!("(or 0 {v})")).unwrap(),
+            Expr::Bool(true),
+            "{v}"
+        );
+    }
+}
+
+#[test]
+fn test_if_on_list() {
+    let interp = Interpreter::new();
+    eval_in(&interp, "(fun [count l] [if l [+ 1 (count (tail l))] [0]])").unwrap();
+    assert_eq!(
+        eval_in(&interp, "(count [1 2 3])").unwrap(),
+        Expr::Number(3)
+    );
+}
+
+#[test]
+fn test_base_predicates() {
+    let interp = Interpreter::new();
+    assert_eq!(
+        eval_in(&interp, "(contains 2 [1 2])").unwrap(),
+        Expr::Bool(true)
+    );
+    assert_eq!(
+        eval_in(&interp, "(contains 3 [1 2])").unwrap(),
+        Expr::Bool(false)
+    );
+    assert_eq!(
+        eval_in(&interp, "(filter (\\ [x] [> x 1]) [1 2 3])")
+            .unwrap()
+            .to_string(),
+        "[2 3]"
+    );
+    assert_eq!(
+        eval_in(&interp, "(map (\\ [x] [x]) [])")
+            .unwrap()
+            .to_string(),
+        "[]"
+    );
+}
@@ -0,0 +1,95 @@
+use jlisp::ast::Expr;
+use jlisp::interpreter::Interpreter;
+use jlisp::span::{Source, Span};
+use jlisp::vm::Evaluator;
+mod common;
+use common::eval_str;
+
+const PROGRAMS: &[&str] = &[
+    "(+ 1 2 3)",
+    "(if (> 2 1) [+ 1 1] [undefined])",
+    "(if (< 2 1) [undefined] [* 2 3])",
+    "((\\ [x y] [- x y]) 10 4)",
+    "(((\\ [x y] [- x y]) 10) 4)",
+    "((\\ [x & xs] [join (list x) xs]) 1 2 3)",
+    "((\\ [& xs] [xs]))",
+    "((\\ [x] [x]))",
+    "(do (def [a] 1) (= [b] 2) (+ a b))",
+    "(eval [+ 1 2])",
+    "(eval [[def [c] 5] [+ c 1]])",
+    "(len (range 100))",
+    "(head [1 2 3])",
+    "(tail [1 2 3])",
+    "(foo 1)",
+    "(1 2 3)",
+    "((\\ [x] [if (== x 0) [[]] [x]]) 0)",
+    "(fun [square x] [* x x])",
+    "(square 9)",
+    "(map square [1 2 3])",
+    "(foldl + 0 (range 100))",
+    "(filter (\\ [x] [> x 2]) [1 2 3 4])",
+    "(select [(== 1 2) 1] [(== 1 1) 2])",
+    "(case 2 [1 10] [2 20])",
+    "(let [do (= [x] 3) (+ x 1)])",
+    "(nth 2 [5 6 7])",
+    "(take 2 [1 2 3])",
+    "(unpack + [1 2 3])",
+    "(pack head 1 2 3)",
+    "(map (\\ [x] [head x]) [1 2])",
+    "(foldl (\\ [a b] [+ a (first b)]) 0 [[1] 2])",
+    "(when (> 2 1) (= [w] 4) (* w w))",
+    "(-> 3 (+ 1) (* 2))",
+    "(macroexpand [unless c x])",
+    "`(a ,(+ 1 2) ,@[b c])",
+    "(defmacro [twice x] `(do ,x ,x))",
+    "(twice (undefined))",
+    "(if [] [1] [nil])",
+    "(list (and true 1) (or nil false) (not \"\"))",
+    "(try (undefined) (catch e (get e \"kind\")) (finally 1))",
+    "(do (import str [capitalize]) (list (capitalize \"vm\") (str/blank? \"\")))",
+];
+
+fn run_all(evaluator: Evaluator) -> Vec<String> {
+    let interp = Interpreter::new().with_evaluator(evaluator);
+    let pe = jlisp::grammar::ExprParser::new();
+    let run = |src: &str| {
+        let source = Source::new("<vm test>", src);
+        let expr = pe.parse(&source, src).unwrap();
+        expr.eval(interp.env(), Span::file(source.file()))
+    };
+
+    // errors include their spans, so both evaluators have to agree on those too
+    PROGRAMS
+        .iter()
+        .map(|src| match run(src) {
+            Ok(v) => v.to_string(),
+            Err(e) => format!("error: {e}"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_vm_matches_tree_walker() {
+    let vm = run_all(Evaluator::Vm);
+    let tree = run_all(Evaluator::TreeWalk);
+    for ((src, v), t) in PROGRAMS.iter().zip(&vm).zip(&tree) {
+        assert_eq!(v, t, "evaluators disagree on {src}");
+    }
+}
+
+#[test]
+fn test_vm_deep_recursion() {
+    // not a tail call, but vm frames live on the heap
+    let res = eval_str(
+        "((\\ [f n] [f f n]) (\\ [self n] [if (== n 0) [0] [+ 1 (self self (- n 1))]]) 100000)",
+    )
+    .unwrap();
+    assert_eq!(res, Expr::Number(100000));
+}
+
+#[test]
+fn test_vm_def_and_assign() {
+    let res = eval_str("(do ((\\ [_] [do (def [g] 1) (= [l] 2)]) 0) (list g))").unwrap();
+    assert_eq!(res, Expr::List(vec![Expr::Number(1)].into()));
+    assert!(eval_str("(do ((\\ [_] [= [l] 2]) 0) l)").is_err());
+}